@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+
+use crate::puzzle::{Answer, Part};
+
+/// Confirmed answers for one year, stored as `<dir>/<year>.toml`:
+///
+/// ```toml
+/// [day_01]
+/// part_1 = "3412094"
+/// ```
+pub struct AnswerBook {
+    path: PathBuf,
+    table: toml::Table,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Missing,
+}
+
+impl AnswerBook {
+    /// A missing file is an empty book, so the first `record` creates it
+    pub fn load(dir: &Path, year: u32) -> anyhow::Result<Self> {
+        let path = dir.join(format!("{}.toml", year));
+        let table = match std::fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .with_context(|| format!("could not parse answers in {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("could not read answers from {}", path.display()));
+            }
+        };
+        Ok(Self { path, table })
+    }
+
+    pub fn get(&self, day: u32, part: Part) -> Option<&str> {
+        self.table.get(&day_key(day))?.get(part_key(part))?.as_str()
+    }
+
+    pub fn check(&self, day: u32, part: Part, answer: &Answer) -> Verdict {
+        match self.get(day, part) {
+            Some(expected) if expected == answer.to_string() => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_owned(),
+            },
+            None => Verdict::Missing,
+        }
+    }
+
+    pub fn record(&mut self, day: u32, part: Part, answer: &Answer) -> anyhow::Result<()> {
+        let day_table = self
+            .table
+            .entry(day_key(day))
+            .or_insert_with(|| toml::Table::new().into())
+            .as_table_mut()
+            .with_context(|| format!("`{}` is not a table", day_key(day)))?;
+        day_table.insert(part_key(part).to_owned(), answer.to_string().into());
+        Ok(())
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&self.path, toml::to_string(&self.table)?)
+            .with_context(|| format!("could not write answers to {}", self.path.display()))
+    }
+}
+
+fn day_key(day: u32) -> String {
+    format!("day_{:02}", day)
+}
+
+fn part_key(part: Part) -> &'static str {
+    match part {
+        Part::One => "part_1",
+        Part::Two => "part_2",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_record_and_check() {
+        let dir = std::env::temp_dir().join(format!("aoc-answers-{}", std::process::id()));
+        let mut book = AnswerBook::load(&dir, 2019).unwrap();
+        assert_eq!(book.check(1, Part::One, &12.into()), Verdict::Missing);
+
+        book.record(1, Part::One, &12.into()).unwrap();
+        book.record(8, Part::Two, &"⬛⬜\n⬜⬛".to_owned().into())
+            .unwrap();
+        book.save().unwrap();
+
+        let book = AnswerBook::load(&dir, 2019).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(book.check(1, Part::One, &12.into()), Verdict::Pass);
+        assert_eq!(
+            book.check(1, Part::One, &13.into()),
+            Verdict::Fail {
+                expected: "12".to_owned()
+            }
+        );
+        assert_eq!(book.check(1, Part::Two, &12.into()), Verdict::Missing);
+        assert_eq!(
+            book.check(8, Part::Two, &"⬛⬜\n⬜⬛".to_owned().into()),
+            Verdict::Pass
+        );
+    }
+}
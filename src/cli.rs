@@ -0,0 +1,291 @@
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+
+use crate::{
+    program::{Val, io::text::Format, limits::Limits},
+    puzzle::{DaySelection, InputSource, Part},
+};
+
+#[derive(Parser)]
+#[command(name = "aoc", about = "Advent of Code solutions")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run a single day, or every implemented day of a year
+    Run(RunArgs),
+    /// Check answers against the confirmed answers in `<answers-dir>/<year>.toml`
+    Verify(VerifyArgs),
+    /// Save confirmed answers, either computed or given with `--answer`
+    Record(RecordArgs),
+    /// Time repeated runs, reporting min/median/max for parsing and each part
+    Bench(BenchArgs),
+    /// Tools for working with Intcode programs directly
+    Intcode {
+        #[command(subcommand)]
+        command: IntcodeCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IntcodeCommand {
+    /// Print an annotated listing of a program
+    Disasm(DisasmArgs),
+    /// Assemble a mnemonic listing into a comma-separated program
+    Asm(AsmArgs),
+    /// Step through a program interactively; type `help` for commands
+    Debug(DebugArgs),
+    /// Run a program, profiling it and optionally writing every executed instruction to a file
+    Trace(TraceArgs),
+    /// Time the Intcode-heavy 2019 days with and without caching decoded instructions
+    Bench(IntcodeBenchArgs),
+    /// Talk to a program that reads and prints ASCII text, typing its input on stdin
+    Ascii(AsciiArgs),
+    /// Run a program, optionally patching its memory first; input comes from `--input` or stdin
+    Run(ProgramArgs),
+    /// Run a program, saving every value it reads and writes as a transcript
+    Record(TranscriptArgs),
+    /// Run a program against a recorded transcript, checking it writes the same outputs
+    Replay(ReplayArgs),
+}
+
+#[derive(Args)]
+pub struct RunArgs {
+    #[arg(long)]
+    pub year: u32,
+    /// Day number, or `all` for every implemented day
+    #[arg(long, default_value = "all")]
+    pub day: DaySelection,
+    /// Only run this part; both parts are run otherwise
+    #[arg(long)]
+    pub part: Option<Part>,
+    /// Read the puzzle input from this file (`-` for stdin) instead of the inputs directory
+    #[arg(long)]
+    pub input: Option<PathBuf>,
+    /// Directory holding `<year>-<day>.txt` puzzle inputs
+    #[arg(long, env = "AOC_INPUTS_DIR", default_value = "inputs")]
+    pub inputs_dir: PathBuf,
+}
+
+impl RunArgs {
+    pub fn input_source(&self) -> InputSource {
+        match &self.input {
+            Some(path) if path.as_os_str() == "-" => InputSource::Stdin,
+            Some(path) => InputSource::File(path.clone()),
+            None => InputSource::Dir(self.inputs_dir.clone()),
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+    #[arg(long, default_value = "answers")]
+    pub answers_dir: PathBuf,
+}
+
+#[derive(Args)]
+pub struct RecordArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+    #[arg(long, default_value = "answers")]
+    pub answers_dir: PathBuf,
+    /// Record this answer instead of computing it; needs a single `--day` and `--part`
+    #[arg(long)]
+    pub answer: Option<String>,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[command(flatten)]
+    pub run: RunArgs,
+    #[arg(long, default_value_t = 10)]
+    pub runs: usize,
+}
+
+#[derive(Args)]
+pub struct IntcodeBenchArgs {
+    /// Comma-separated days to time
+    #[arg(long, value_delimiter = ',', default_values_t = [13, 17, 19])]
+    pub days: Vec<u32>,
+    /// Directory holding `<year>-<day>.txt` puzzle inputs
+    #[arg(long, env = "AOC_INPUTS_DIR", default_value = "inputs")]
+    pub inputs_dir: PathBuf,
+    #[arg(long, default_value_t = 10)]
+    pub runs: usize,
+}
+
+#[derive(Args)]
+pub struct DisasmArgs {
+    /// File holding the comma-separated program (`-` for stdin)
+    pub file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct AsmArgs {
+    /// File holding the assembly source (`-` for stdin)
+    pub file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct DebugArgs {
+    /// File holding the comma-separated program; commands are read from stdin
+    pub file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct AsciiArgs {
+    /// File holding the comma-separated program; input lines are read from stdin
+    pub file: PathBuf,
+    /// Type the lines of this file before reading from stdin
+    #[arg(long)]
+    pub script: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ProgramArgs {
+    /// File holding the comma-separated program (`-` for stdin, if `--input` is given)
+    pub file: PathBuf,
+    /// Overwrite memory before running, e.g. `--set 0=2`; may be repeated
+    #[arg(long, value_name = "ADDR=VAL", allow_negative_numbers = true)]
+    pub set: Vec<Patch>,
+    /// Comma-separated values to feed the program instead of reading stdin
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    pub input: Vec<Val>,
+    /// How input on stdin is written
+    #[arg(long, default_value = "lines")]
+    pub in_format: Format,
+    /// How to write output to stdout
+    #[arg(long, default_value = "lines")]
+    pub out_format: Format,
+    /// After the program halts, print the value at address 0
+    #[arg(long)]
+    pub result: bool,
+    /// After the program halts, print its whole memory, comma-separated
+    #[arg(long)]
+    pub dump: bool,
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+/// A change to a program's memory, written `ADDR=VAL`
+#[derive(Clone, Copy, Debug)]
+pub struct Patch {
+    pub addr: usize,
+    pub val: Val,
+}
+
+impl FromStr for Patch {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (addr, val) = s
+            .split_once('=')
+            .with_context(|| format!("expected `ADDR=VAL`, got `{}`", s))?;
+        Ok(Self {
+            addr: addr
+                .trim()
+                .parse()
+                .with_context(|| format!("`{}` is not an address", addr))?,
+            val: val
+                .trim()
+                .parse()
+                .with_context(|| format!("`{}` is not a value", val))?,
+        })
+    }
+}
+
+#[derive(Args)]
+pub struct TranscriptArgs {
+    /// File holding the comma-separated program
+    pub file: PathBuf,
+    /// Comma-separated values to feed the program; otherwise read one per line from stdin
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    pub input: Vec<Val>,
+    /// Write the transcript here
+    #[arg(long)]
+    pub out: PathBuf,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// File holding the comma-separated program
+    pub file: PathBuf,
+    /// Transcript written by `intcode record`
+    pub transcript: PathBuf,
+}
+
+/// Bounds on how long a program may run
+#[derive(Args)]
+pub struct LimitArgs {
+    /// Stop the program after this many instructions
+    #[arg(long)]
+    pub max_steps: Option<u64>,
+    /// Stop the program after this many seconds
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<f64>,
+}
+
+impl LimitArgs {
+    pub fn limits(&self) -> Limits {
+        Limits {
+            max_steps: self.max_steps,
+            timeout: self.timeout.map(Duration::from_secs_f64),
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct TraceArgs {
+    /// File holding the comma-separated program (`-` for stdin)
+    pub file: PathBuf,
+    /// Comma-separated values to feed the program
+    #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+    pub input: Vec<String>,
+    /// Write the instruction trace here
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+    /// Machine word to run the program with
+    #[arg(long, default_value = "i128")]
+    pub word: WordSize,
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+/// Word types the VM can run with; overflowing a fixed-size word is an error
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum WordSize {
+    I64,
+    I128,
+    /// Arbitrary precision, which never overflows
+    Big,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_patch() {
+        let patch: Patch = "12=-3".parse().unwrap();
+        assert_eq!((patch.addr, patch.val), (12, -3));
+        let patch: Patch = " 0 = 2 ".parse().unwrap();
+        assert_eq!((patch.addr, patch.val), (0, 2));
+
+        let err = |s: &str| s.parse::<Patch>().unwrap_err().to_string();
+        assert_eq!(err("12"), "expected `ADDR=VAL`, got `12`");
+        assert_eq!(err("-1=5"), "`-1` is not an address");
+        assert_eq!(err("x=5"), "`x` is not an address");
+        assert_eq!(err("1=five"), "`five` is not a value");
+        assert_eq!(
+            err("1=170141183460469231731687303715884105728"),
+            "`170141183460469231731687303715884105728` is not a value"
+        );
+    }
+}
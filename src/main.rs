@@ -2,19 +2,36 @@
 #![feature(try_blocks)]
 #![feature(iter_intersperse)]
 
+use clap::Parser;
+
+use cli::{Cli, Command, IntcodeCommand};
+
+mod answers;
+mod cli;
 pub mod maze;
 mod program;
+mod puzzle;
+mod runner;
 mod utls;
 mod year_2019;
 
-fn main() {
-    run_year("2019");
-
-    println!("Hello, world!");
-}
-fn run_year(year: &str) {
-    match year {
-        "2019" => year_2019::run(),
-        _ => unreachable!("year not implemented"),
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Run(args) => runner::run(&args),
+        Command::Verify(args) => runner::verify(&args),
+        Command::Record(args) => runner::record(&args),
+        Command::Bench(args) => runner::bench(&args),
+        Command::Intcode { command } => match command {
+            IntcodeCommand::Disasm(args) => runner::disasm(&args),
+            IntcodeCommand::Asm(args) => runner::asm(&args),
+            IntcodeCommand::Debug(args) => runner::debug(&args),
+            IntcodeCommand::Trace(args) => runner::trace(&args),
+            IntcodeCommand::Bench(args) => runner::intcode_bench(&args),
+            IntcodeCommand::Ascii(args) => runner::ascii(&args),
+            IntcodeCommand::Run(args) => runner::run_program(&args),
+            IntcodeCommand::Record(args) => runner::record_transcript(&args),
+            IntcodeCommand::Replay(args) => runner::replay(&args),
+        },
     }
 }
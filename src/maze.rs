@@ -62,7 +62,7 @@ impl Maze {
 }
 
 impl MyParse for Maze {
-    fn my_parse(s: &str) -> Self {
+    fn my_parse(s: &str) -> anyhow::Result<Self> {
         let mut points = HashSet::new();
         let mut things = HashMap::new();
         let mut underlying_grid = vec![];
@@ -86,10 +86,10 @@ impl MyParse for Maze {
             }
             underlying_grid.push(cell_row);
         }
-        Self {
+        Ok(Self {
             points,
             things,
             underlying_grid,
-        }
+        })
     }
 }
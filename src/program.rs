@@ -1,87 +1,283 @@
+pub mod asm;
+pub mod debugger;
+pub mod disasm;
+pub mod error;
+pub mod executor;
 pub mod io;
+pub mod limits;
+pub mod machine;
+pub mod memory;
+pub mod network;
 mod opcode;
+pub mod packet;
+pub mod snapshot;
 pub mod spawn;
+pub mod terminal;
+pub mod trace;
+pub mod word;
 
-use crate::program::io::TryReadVal;
 use crate::program::io::TryWriteVal;
+use crate::program::io::{Joint, TryReadVal};
 
 use crate::utls::MyParse;
-use opcode::Opcode;
+use anyhow::Context;
+use error::{VmError, VmErrorKind};
+use limits::{Budget, Limits};
+use memory::Memory;
+use opcode::{DecodeCache, Opcode};
+use trace::{NoTrace, Tracer};
+use word::Word;
 
 pub type Val = i128;
-const CODE_PAD: usize = 10;
 
 #[derive(Default, Clone)]
-
-pub struct Program {
-    pub code: Vec<Val>,
+pub struct Program<W = Val> {
+    pub memory: Memory<W>,
+    decoded: Option<DecodeCache<W>>,
 }
 
-#[derive(Default)]
-pub struct ProgramState {
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct ProgramState<W = Val> {
     offset: usize,
-    relative_base: usize,
+    relative_base: W,
+}
+
+/// What happened when stepping a program; blocked steps leave the state untouched so they can be retried
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepOutcome {
+    Continue,
+    Halted,
+    NeedsInput,
+    OutputBlocked,
 }
 
-impl MyParse for Program {
-    fn my_parse(s: &str) -> Self {
-        Program::new(Self::parse_code(s))
+impl<W: Word> MyParse for Program<W> {
+    fn my_parse(s: &str) -> anyhow::Result<Self> {
+        Ok(Program::new(Self::parse_code(s)?))
     }
 }
 
-impl Program {
-    pub fn step<Io: TryReadVal + TryWriteVal>(
+impl<W: Word> Program<W> {
+    pub fn step<Io: TryReadVal<W> + TryWriteVal<W>>(
         &mut self,
-        program_state: ProgramState,
+        program_state: &mut ProgramState<W>,
         io: &mut Io,
-    ) -> Option<ProgramState> {
-        let opcode_raw = self.code[program_state.offset];
-        let opcode = Opcode::new(opcode_raw);
-        opcode.eval(self, program_state, io)
+    ) -> Result<StepOutcome, VmError<W>> {
+        self.step_traced(program_state, io, &mut NoTrace)
     }
 
-    pub fn init(&mut self, noun: Val, verb: Val) {
-        self.code[1] = noun;
-        self.code[2] = verb;
+    /// `step`, reporting the instruction to `tracer` if it executes
+    pub fn step_traced<Io: TryReadVal<W> + TryWriteVal<W>, T: Tracer<W>>(
+        &mut self,
+        program_state: &mut ProgramState<W>,
+        io: &mut Io,
+        tracer: &mut T,
+    ) -> Result<StepOutcome, VmError<W>> {
+        let ip = program_state.offset;
+        let opcode = match &mut self.decoded {
+            // far-off code is rare, and caching it would mean a cache as big as its address
+            Some(cache) if ip < self.memory.dense().len() => cache.decode(ip, &self.memory[ip]),
+            _ => Opcode::new(&self.memory[ip]),
+        };
+        opcode
+            .and_then(|opcode| opcode.eval(self, program_state, io, tracer))
+            .map_err(|kind| self.fault(ip, kind))
     }
 
-    pub fn eval_joint<Io: TryReadVal + TryWriteVal>(&mut self, io: &mut Io) -> Val {
-        let mut program_state = ProgramState::default();
-        while let Some(next_program_state) = self.step(program_state, io) {
-            program_state = next_program_state;
+    /// An error for the instruction at `ip`
+    fn fault(&self, ip: usize, kind: VmErrorKind<W>) -> VmError<W> {
+        VmError {
+            ip,
+            opcode: self.memory[ip].clone(),
+            kind,
         }
-        self.code[0]
     }
 
-    pub fn eval<W: TryWriteVal, R: TryReadVal>(&mut self, input: &mut R, output: &mut W) -> Val {
-        struct JointValMut<'a, W, R> {
-            input: &'a mut R,
-            output: &'a mut W,
+    pub fn init(&mut self, noun: W, verb: W) {
+        self.memory[1] = noun;
+        self.memory[2] = verb;
+    }
+
+    /// Run to completion, treating blocked I/O as an error and stopping once `limits` are exceeded
+    pub fn eval_joint_limited<Io: TryReadVal<W> + TryWriteVal<W>>(
+        &mut self,
+        io: &mut Io,
+        limits: Limits,
+    ) -> Result<W, VmError<W>> {
+        let mut program_state = ProgramState::default();
+        let mut budget = Budget::new(limits);
+        loop {
+            let outcome = match budget.tick() {
+                Ok(()) => self.step(&mut program_state, io)?,
+                Err(kind) => return Err(self.fault(program_state.offset, kind)),
+            };
+            let kind = match outcome {
+                StepOutcome::Continue => continue,
+                StepOutcome::Halted => return Ok(self.memory[0].clone()),
+                StepOutcome::NeedsInput => VmErrorKind::InputUnavailable,
+                StepOutcome::OutputBlocked => VmErrorKind::OutputUnavailable,
+            };
+            return Err(self.fault(program_state.offset, kind));
         }
+    }
 
-        impl<W, R: TryReadVal> TryReadVal for JointValMut<'_, W, R> {
-            fn try_read_val(&mut self) -> Option<Val> {
-                self.input.try_read_val()
-            }
+    pub fn eval<Out: TryWriteVal<W>, In: TryReadVal<W>>(
+        &mut self,
+        input: &mut In,
+        output: &mut Out,
+    ) -> Result<W, VmError<W>> {
+        self.eval_limited(input, output, Limits::default())
+    }
+
+    pub fn eval_limited<Out: TryWriteVal<W>, In: TryReadVal<W>>(
+        &mut self,
+        input: &mut In,
+        output: &mut Out,
+        limits: Limits,
+    ) -> Result<W, VmError<W>> {
+        let mut joint = Joint { input, output };
+
+        self.eval_joint_limited(&mut joint, limits)
+    }
+
+    fn new(code: Vec<W>) -> Self {
+        Self {
+            memory: Memory::new(code),
+            decoded: None,
         }
+    }
 
-        impl<W: TryWriteVal, R> TryWriteVal for JointValMut<'_, W, R> {
-            fn try_write_val(&mut self, val: Val) -> Option<()> {
-                self.output.try_write_val(val)
-            }
+    /// Remember decoded instructions instead of decoding each one every time it runs
+    pub fn with_decode_cache(self, enabled: bool) -> Self {
+        Self {
+            decoded: enabled.then(DecodeCache::default),
+            ..self
         }
+    }
 
-        let mut joint = JointValMut { input, output };
+    pub(crate) fn parse_code(s: &str) -> anyhow::Result<Vec<W>> {
+        // 1-based column of the current value, for error reporting
+        let mut column = 1;
+        s.trim()
+            .split(",")
+            .map(|x| {
+                let start = column;
+                column += x.len() + 1;
+                x.trim()
+                    .parse()
+                    .with_context(|| format!("column {}: `{}` is not an integer", start, x.trim()))
+            })
+            .collect()
+    }
+}
 
-        self.eval_joint(&mut joint)
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::io::{View, infallible::Unused};
+
+    fn fault(code: &str) -> VmError {
+        Program::my_parse(code)
+            .unwrap()
+            .eval(&mut Unused, &mut Unused)
+            .unwrap_err()
     }
 
-    fn new(mut code: Vec<Val>) -> Self {
-        code.resize(code.len() * CODE_PAD, 0);
-        Self { code }
+    #[test]
+    fn test_faults() {
+        let err = fault("1,0,0,0,42");
+        assert_eq!((err.ip, err.opcode), (4, 42));
+        assert_eq!(err.kind, VmErrorKind::UnknownOpcode);
+        assert_eq!(err.to_string(), "ip 4 (opcode 42): unknown opcode");
+
+        assert_eq!(fault("301,0,0,0,99").kind, VmErrorKind::BadMode(3));
+        assert_eq!(fault("1,-1,0,0,99").kind, VmErrorKind::NegativeAddress(-1));
+        assert_eq!(fault("11101,1,1,0,99").kind, VmErrorKind::ImmediateWrite(2));
+        assert_eq!(
+            fault("4,100000000000000000000,99").kind,
+            VmErrorKind::OutOfRange(100000000000000000000)
+        );
+        assert_eq!(fault("3,0,99").kind, VmErrorKind::InputUnavailable);
+
+        // an operand past the last address
+        let err = fault("1101,4,0,18446744073709551615,1105,1,18446744073709551615");
+        assert_eq!((err.ip, err.opcode), (usize::MAX, 4));
+        assert_eq!(err.kind, VmErrorKind::OutOfRange(18446744073709551616));
+        // the next instruction past the last address
+        let err = fault("1101,9,0,18446744073709551614,1105,1,18446744073709551614");
+        assert_eq!((err.ip, err.opcode), (usize::MAX - 1, 9));
+        assert_eq!(err.kind, VmErrorKind::OutOfRange(18446744073709551616));
     }
 
-    pub(crate) fn parse_code(s: &str) -> Vec<Val> {
-        s.trim().split(",").map(|x| x.parse().unwrap()).collect()
+    #[test]
+    fn test_word_size() {
+        let code = "1102,9223372036854775807,2,0,99";
+        let err = Program::<i64>::my_parse(code)
+            .unwrap()
+            .eval(&mut Unused, &mut Unused)
+            .unwrap_err();
+        assert_eq!((err.ip, err.opcode), (0, 1102));
+        assert_eq!(err.kind, VmErrorKind::Overflow);
+
+        // a relative base that overflows is caught as well
+        let err = Program::<i64>::my_parse("109,9223372036854775807,109,1,99")
+            .unwrap()
+            .eval(&mut Unused, &mut Unused)
+            .unwrap_err();
+        assert_eq!((err.ip, err.kind), (2, VmErrorKind::Overflow));
+
+        let doubled = Program::<i128>::my_parse(code)
+            .unwrap()
+            .eval(&mut Unused, &mut Unused);
+        assert_eq!(doubled, Ok(2 * i64::MAX as i128));
+
+        // squaring fits in an i128, but quadrupling that only fits arbitrary precision
+        let code = "1102,9223372036854775807,9223372036854775807,0,1002,0,4,0,99";
+        let err = Program::<i128>::my_parse(code)
+            .unwrap()
+            .eval(&mut Unused, &mut Unused)
+            .unwrap_err();
+        assert_eq!((err.ip, err.kind), (4, VmErrorKind::Overflow));
+        let big = Program::<num::BigInt>::my_parse(code)
+            .unwrap()
+            .eval(&mut Unused, &mut Unused);
+        assert_eq!(big, Ok(num::BigInt::from(i64::MAX).pow(2) * 4));
+    }
+
+    #[test]
+    fn test_decode_cache_sees_self_modification() {
+        // bumps [17], then overwrites its first instruction with a halt and jumps back to it
+        let mut program: Program =
+            Program::my_parse("1001,17,1,17,1101,99,0,0,1105,1,0,99,0,0,0,0,0,5").unwrap();
+        program = program.with_decode_cache(true);
+        let res = program.eval_limited(&mut Unused, &mut Unused, Limits::steps(100));
+        assert_eq!(res, Ok(99));
+        assert_eq!(program.memory[17], 6);
+
+        // writes a halt far past the image and jumps to it
+        let program: Program =
+            Program::my_parse("1101,99,0,100000000000,1105,1,100000000000").unwrap();
+        let res = program.with_decode_cache(true).eval_limited(
+            &mut Unused,
+            &mut Unused,
+            Limits::steps(100),
+        );
+        assert_eq!(res, Ok(1101));
+    }
+
+    #[test]
+    fn test_step_blocks_without_advancing() {
+        let mut program = Program::my_parse("3,5,4,5,99,0").unwrap();
+        let mut state = ProgramState::default();
+        assert_eq!(
+            program.step(&mut state, &mut Unused),
+            Ok(StepOutcome::NeedsInput)
+        );
+        assert_eq!(state.offset, 0);
+
+        let mut output: Vec<Val> = vec![];
+        let mut input = View::new(vec![7]);
+        assert_eq!(program.eval(&mut input, &mut output), Ok(3));
+        assert_eq!(output, vec![7]);
     }
 }
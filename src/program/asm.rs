@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use super::{
+    Program, Val,
+    opcode::{InstructionMode, OpcodeVariant},
+};
+use crate::utls::parse_lines;
+
+/// An assembled word, which may refer to a label's address
+enum Word {
+    Lit(Val),
+    Label(String),
+}
+
+struct Line {
+    labels: Vec<String>,
+    words: Vec<Word>,
+}
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_value(s: &str) -> anyhow::Result<Word> {
+    if let Ok(val) = s.parse() {
+        Ok(Word::Lit(val))
+    } else if is_label(s) {
+        Ok(Word::Label(s.to_owned()))
+    } else {
+        anyhow::bail!("`{}` is neither a number nor a label", s)
+    }
+}
+
+fn parse_operand(s: &str) -> anyhow::Result<(InstructionMode, Word)> {
+    let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) else {
+        return Ok((InstructionMode::Immediate, parse_value(s)?));
+    };
+    let relative = inner
+        .strip_prefix("rb")
+        .filter(|offset| offset.is_empty() || offset.trim_start().starts_with(['+', '-']));
+    let Some(offset) = relative else {
+        return Ok((InstructionMode::Parameter, parse_value(inner.trim())?));
+    };
+    let offset = offset.replace(' ', "");
+    let offset = match offset.strip_prefix('+').unwrap_or(&offset) {
+        "" => 0,
+        offset => offset
+            .parse()
+            .with_context(|| format!("`{}` is not a relative offset", s))?,
+    };
+    Ok((InstructionMode::Relative, Word::Lit(offset)))
+}
+
+fn parse_line(line: &str) -> anyhow::Result<Line> {
+    let mut rest = line.split(['#', ';']).next().unwrap_or_default().trim();
+    let mut labels = vec![];
+    while let Some((label, after)) = rest.split_once(':') {
+        let label = label.trim();
+        // disassembler listings start with an address column, which is skipped
+        if !label.chars().all(|c| c.is_ascii_digit()) {
+            anyhow::ensure!(is_label(label), "`{}` is not a valid label", label);
+            labels.push(label.to_owned());
+        }
+        rest = after.trim();
+    }
+
+    let (mnemonic, operands) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let operands = operands
+        .split(',')
+        .map(str::trim)
+        .filter(|operand| !operand.is_empty())
+        .collect_vec();
+    let words = match mnemonic {
+        "" => vec![],
+        "data" => {
+            anyhow::ensure!(!operands.is_empty(), "`data` needs at least one value");
+            operands.into_iter().map(parse_value).try_collect()?
+        }
+        mnemonic => {
+            let variant = OpcodeVariant::iter()
+                .find(|variant| variant.to_string() == mnemonic)
+                .with_context(|| format!("unknown mnemonic `{}`", mnemonic))?;
+            anyhow::ensure!(
+                operands.len() == variant.instruction_count(),
+                "`{}` takes {} operands, found {}",
+                mnemonic,
+                variant.instruction_count(),
+                operands.len()
+            );
+            let mut opcode = variant as Val;
+            let mut words = vec![];
+            for (param, operand) in operands.into_iter().enumerate() {
+                let (mode, word) = parse_operand(operand)?;
+                anyhow::ensure!(
+                    !(matches!(mode, InstructionMode::Immediate)
+                        && variant.write_param() == Some(param)),
+                    "`{}` writes to its operand `{}`, which needs to be an address",
+                    mnemonic,
+                    operand
+                );
+                opcode += mode as Val * 10_i128.pow(param as u32 + 2);
+                words.push(word);
+            }
+            words.insert(0, Word::Lit(opcode));
+            words
+        }
+    };
+    Ok(Line { labels, words })
+}
+
+/// Assemble the mnemonic syntax printed by the disassembler.
+///
+/// Besides instructions, a line may hold `name:` labels (usable anywhere a number
+/// is), `data` directives with comma-separated values, and `#`/`;` comments.
+pub fn assemble(src: &str) -> anyhow::Result<Program> {
+    let lines = parse_lines(src, parse_line)?;
+
+    let mut labels = HashMap::new();
+    let mut addr = 0;
+    for line in &lines {
+        for label in &line.labels {
+            anyhow::ensure!(
+                labels.insert(label.as_str(), addr).is_none(),
+                "label `{}` is defined more than once",
+                label
+            );
+        }
+        addr += line.words.len();
+    }
+
+    let code = lines
+        .iter()
+        .flat_map(|line| &line.words)
+        .map(|word| match word {
+            Word::Lit(val) => Ok(*val),
+            Word::Label(label) => labels
+                .get(label.as_str())
+                .map(|&addr| addr as Val)
+                .with_context(|| format!("undefined label `{}`", label)),
+        })
+        .try_collect()?;
+    Ok(Program::new(code))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        program::{disasm::listing, io::View},
+        utls::MyParse,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let code = Program::parse_code("1002,4,3,4,33,109,-1,21101,7,8,-2,204,1,99,123").unwrap();
+        let program = assemble(&listing(&code)).unwrap();
+        assert_eq!(program.memory, Program::new(code).memory);
+    }
+
+    #[test]
+    fn test_labels() {
+        let program = assemble(
+            "
+            # outputs 1 if the input is 8, otherwise 0
+            start:  in [x]
+                    eq [x], 8, [x]
+                    out [x]
+                    hlt
+            x:      data 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            program.memory,
+            Program::my_parse("3,9,1008,9,8,9,4,9,99,0").unwrap().memory
+        );
+
+        let mut output: Vec<Val> = vec![];
+        program
+            .clone()
+            .eval(&mut View::new(vec![8]), &mut output)
+            .unwrap();
+        program
+            .clone()
+            .eval(&mut View::new(vec![7]), &mut output)
+            .unwrap();
+        assert_eq!(output, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = assemble("hlt\nadd 1, 2").err().unwrap();
+        assert_eq!(
+            format!("{:#}", err),
+            "line 2: `add 1, 2`: `add` takes 3 operands, found 2"
+        );
+        let err = assemble("jt 1, nowhere").err().unwrap();
+        assert_eq!(err.to_string(), "undefined label `nowhere`");
+        let err = assemble("push 1").err().unwrap();
+        assert_eq!(
+            format!("{:#}", err),
+            "line 1: `push 1`: unknown mnemonic `push`"
+        );
+        let err = assemble("hlt\nadd 1, 2, 3").err().unwrap();
+        assert_eq!(
+            format!("{:#}", err),
+            "line 2: `add 1, 2, 3`: `add` writes to its operand `3`, which needs to be an address"
+        );
+        let err = assemble("in 5").err().unwrap();
+        assert_eq!(
+            format!("{:#}", err),
+            "line 1: `in 5`: `in` writes to its operand `5`, which needs to be an address"
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{BufRead, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::Context;
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use super::{
+    Program, Val,
+    disasm::Item,
+    error::VmError,
+    machine::{Event, Machine},
+    opcode::{Opcode, OpcodeVariant},
+    snapshot::Snapshot,
+};
+
+const HELP: &str = "\
+step [N]          execute N instructions (default 1)
+continue          run until a breakpoint, watchpoint, halt or missing input
+break ADDR|OP     stop before the instruction at ADDR, or any OP (e.g. `break out`)
+delete ADDR|OP    remove a breakpoint
+watch ADDR        stop after the cell at ADDR changes
+unwatch ADDR      remove a watchpoint
+input VAL,...     queue input for the program
+regs              show the instruction pointer and relative base
+mem ADDR [N]      show N cells starting at ADDR (default 1)
+set ADDR VAL      overwrite the cell at ADDR
+disasm [N]        disassemble N instructions from the instruction pointer (default 5)
+save FILE         write a snapshot of the machine to FILE
+load FILE         continue from a snapshot written by `save`
+quit              leave the debugger";
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
+enum Breakpoint {
+    #[display("address {_0}")]
+    Addr(usize),
+    #[display("opcode `{_0}`")]
+    Opcode(String),
+}
+
+impl FromStr for Breakpoint {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(addr) = s.parse() {
+            Ok(Breakpoint::Addr(addr))
+        } else if OpcodeVariant::iter().any(|variant| variant.to_string() == s) {
+            Ok(Breakpoint::Opcode(s.to_owned()))
+        } else {
+            anyhow::bail!("`{}` is neither an address nor a mnemonic", s)
+        }
+    }
+}
+
+enum Command {
+    Step(usize),
+    Continue,
+    Break(Breakpoint),
+    Delete(Breakpoint),
+    Watch(usize),
+    Unwatch(usize),
+    Input(Vec<Val>),
+    Regs,
+    Mem(usize, usize),
+    Set(usize, Val),
+    Disasm(usize),
+    Save(PathBuf),
+    Load(PathBuf),
+    Help,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn arg<T: FromStr>(arg: Option<&str>, default: Option<T>) -> anyhow::Result<T>
+        where
+            T::Err: Into<anyhow::Error>,
+        {
+            match arg {
+                Some(arg) => arg
+                    .parse()
+                    .map_err(Into::into)
+                    .with_context(|| format!("bad argument `{}`", arg)),
+                None => default.context("missing argument"),
+            }
+        }
+
+        let mut words = s.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let (a, b) = (words.next(), words.next());
+        Ok(match command {
+            "s" | "step" => Command::Step(arg(a, Some(1))?),
+            "c" | "continue" => Command::Continue,
+            "b" | "break" => Command::Break(arg(a, None)?),
+            "d" | "delete" => Command::Delete(arg(a, None)?),
+            "w" | "watch" => Command::Watch(arg(a, None)?),
+            "unwatch" => Command::Unwatch(arg(a, None)?),
+            "i" | "input" => Command::Input(Program::parse_code(&s[command.len()..])?),
+            "r" | "regs" => Command::Regs,
+            "m" | "mem" => Command::Mem(arg(a, None)?, arg(b, Some(1))?),
+            "set" => Command::Set(arg(a, None)?, arg(b, None)?),
+            "dis" | "disasm" => Command::Disasm(arg(a, Some(5))?),
+            "save" => Command::Save(arg(a, None)?),
+            "load" => Command::Load(arg(a, None)?),
+            "h" | "help" => Command::Help,
+            "q" | "quit" => Command::Quit,
+            command => anyhow::bail!("unknown command `{}`; try `help`", command),
+        })
+    }
+}
+
+/// Why execution stopped before finishing the requested steps
+#[derive(derive_more::Display)]
+enum Stop {
+    #[display("hit breakpoint on {_0}")]
+    Breakpoint(Breakpoint),
+    #[display("cell {addr} changed from {old} to {new}")]
+    Watch { addr: usize, old: Val, new: Val },
+    #[display("program is waiting for input; queue some with `input`")]
+    NeedsInput,
+    #[display("program halted")]
+    Halted,
+    #[display("program faulted: {_0}")]
+    Fault(VmError),
+}
+
+/// A REPL around a `Machine` for stepping through a program
+pub struct Debugger {
+    machine: Machine,
+    breakpoints: BTreeSet<Breakpoint>,
+    /// Watched addresses with the last value seen at each
+    watches: BTreeMap<usize, Val>,
+}
+
+impl Debugger {
+    pub fn new(program: Program) -> Self {
+        Self {
+            machine: Machine::new(program),
+            breakpoints: BTreeSet::new(),
+            watches: BTreeMap::new(),
+        }
+    }
+
+    /// Read commands from `input` until it ends or the user quits
+    pub fn repl(&mut self, input: impl BufRead, mut out: impl Write) -> anyhow::Result<()> {
+        self.show_current(&mut out)?;
+        write!(out, "(icdb) ")?;
+        out.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                let res = match line.trim().parse() {
+                    Ok(Command::Quit) => return Ok(()),
+                    Ok(command) => self.execute(command, &mut out),
+                    Err(err) => Err(err),
+                };
+                if let Err(err) = res {
+                    writeln!(out, "{:#}", err)?;
+                }
+            }
+            write!(out, "(icdb) ")?;
+            out.flush()?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+
+    fn execute(&mut self, command: Command, out: &mut impl Write) -> anyhow::Result<()> {
+        match command {
+            Command::Step(count) => {
+                for _ in 0..count {
+                    if let Some(stop) = self.step(out)? {
+                        writeln!(out, "{}", stop)?;
+                        break;
+                    }
+                }
+                self.show_current(out)?;
+            }
+            Command::Continue => {
+                let mut stop = self.step(out)?;
+                while stop.is_none() {
+                    stop = match self.breakpoint() {
+                        Some(breakpoint) => Some(Stop::Breakpoint(breakpoint)),
+                        None => self.step(out)?,
+                    };
+                }
+                writeln!(out, "{}", stop.unwrap())?;
+                self.show_current(out)?;
+            }
+            Command::Break(breakpoint) => {
+                writeln!(out, "breakpoint on {}", breakpoint)?;
+                self.breakpoints.insert(breakpoint);
+            }
+            Command::Delete(breakpoint) => {
+                if !self.breakpoints.remove(&breakpoint) {
+                    writeln!(out, "no breakpoint on {}", breakpoint)?;
+                }
+            }
+            Command::Watch(addr) => {
+                let val = self.machine.memory()[addr];
+                writeln!(out, "watching cell {} (currently {})", addr, val)?;
+                self.watches.insert(addr, val);
+            }
+            Command::Unwatch(addr) => {
+                if self.watches.remove(&addr).is_none() {
+                    writeln!(out, "cell {} is not watched", addr)?;
+                }
+            }
+            Command::Input(vals) => {
+                for val in vals {
+                    self.machine.provide_input(val);
+                }
+            }
+            Command::Regs => {
+                writeln!(
+                    out,
+                    "ip {}  rb {}",
+                    self.machine.ip(),
+                    self.machine.relative_base()
+                )?;
+            }
+            Command::Mem(addr, count) => {
+                let Some(end) = addr.checked_add(count) else {
+                    writeln!(
+                        out,
+                        "{} cells from {} run past the end of memory",
+                        count, addr
+                    )?;
+                    return Ok(());
+                };
+                let memory = self.machine.memory();
+                for row in &(addr..end).chunks(8) {
+                    let row = row.collect_vec();
+                    let vals = row.iter().map(|&addr| memory[addr]).join(" ");
+                    writeln!(out, "{:04}: {}", row[0], vals)?;
+                }
+            }
+            Command::Set(addr, val) => {
+                self.machine.memory_mut()[addr] = val;
+                if let Some(seen) = self.watches.get_mut(&addr) {
+                    *seen = val;
+                }
+            }
+            Command::Disasm(count) => {
+                let mut addr = self.machine.ip();
+                for _ in 0..count {
+                    let item = Item::decode_memory(self.machine.memory(), addr);
+                    writeln!(out, "{:04}: {}", addr, item)?;
+                    match addr.checked_add(item.len()) {
+                        Some(next) => addr = next,
+                        None => break,
+                    }
+                }
+            }
+            Command::Save(path) => {
+                std::fs::write(&path, self.machine.snapshot().to_string())
+                    .with_context(|| format!("could not write {}", path.display()))?;
+            }
+            Command::Load(path) => {
+                let snapshot: Snapshot = std::fs::read_to_string(&path)
+                    .with_context(|| format!("could not read {}", path.display()))?
+                    .parse()
+                    .with_context(|| format!("bad snapshot in {}", path.display()))?;
+                self.machine.restore(&snapshot);
+                self.show_current(out)?;
+            }
+            Command::Help => writeln!(out, "{}", HELP)?,
+            Command::Quit => unreachable!("handled by the repl"),
+        }
+        Ok(())
+    }
+
+    /// Execute one instruction, reporting why execution should stop, if it should
+    fn step(&mut self, out: &mut impl Write) -> anyhow::Result<Option<Stop>> {
+        let mut stop = match self.machine.step() {
+            Ok(Some(Event::Output(val))) => {
+                writeln!(out, "output: {}", val)?;
+                None
+            }
+            Ok(Some(Event::NeedsInput)) => Some(Stop::NeedsInput),
+            Ok(Some(Event::Halted)) => Some(Stop::Halted),
+            Ok(None) => None,
+            Err(err) => Some(Stop::Fault(err)),
+        };
+        for (&addr, seen) in &mut self.watches {
+            let new = self.machine.memory()[addr];
+            if new != *seen {
+                let old = std::mem::replace(seen, new);
+                stop = stop.or(Some(Stop::Watch { addr, old, new }));
+            }
+        }
+        Ok(stop)
+    }
+
+    /// The breakpoint on the next instruction, if any
+    fn breakpoint(&self) -> Option<Breakpoint> {
+        let ip = self.machine.ip();
+        let addr = Breakpoint::Addr(ip);
+        if self.breakpoints.contains(&addr) {
+            return Some(addr);
+        }
+        let opcode = Opcode::new(&self.machine.memory()[ip]).ok()?;
+        let opcode = Breakpoint::Opcode(opcode.variant.to_string());
+        self.breakpoints.contains(&opcode).then_some(opcode)
+    }
+
+    fn show_current(&self, out: &mut impl Write) -> anyhow::Result<()> {
+        let ip = self.machine.ip();
+        let item = Item::decode_memory(self.machine.memory(), ip);
+        writeln!(out, "=> {:04}: {}", ip, item)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{program::asm::assemble, utls::MyParse};
+
+    fn session(commands: &str) -> String {
+        let program = assemble(
+            "
+            loop:   in [x]
+                    jf [x], end
+                    mul 2, [x], [x]
+                    out [x]
+                    jt 1, loop
+            end:    hlt
+            x:      data 0
+            ",
+        )
+        .unwrap();
+        session_with(program, commands)
+    }
+
+    fn session_with(program: Program, commands: &str) -> String {
+        let mut out = vec![];
+        Debugger::new(program)
+            .repl(commands.as_bytes(), &mut out)
+            .unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| line.trim_start_matches("(icdb) "))
+            .filter(|line| !line.is_empty())
+            .join("\n")
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let out = session("break out\ncontinue\ninput 3\nc\nregs\nc\nset 15 5\nmem 14 2\nc\nq\n");
+        assert_eq!(
+            out,
+            [
+                "=> 0000: in [15]",
+                "breakpoint on opcode `out`",
+                "program is waiting for input; queue some with `input`",
+                "=> 0000: in [15]",
+                "hit breakpoint on opcode `out`",
+                "=> 0009: out [15]",
+                "ip 9  rb 0",
+                "output: 6",
+                "program is waiting for input; queue some with `input`",
+                "=> 0000: in [15]",
+                "0014: 99 5",
+                "program is waiting for input; queue some with `input`",
+                "=> 0000: in [15]",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_watch_and_step() {
+        let out = session(
+            "watch 15\ninput 4,0\nstep 10\ns\ndisasm 2\nbogus\nmem 18446744073709551615 2\n",
+        );
+        assert_eq!(
+            out,
+            [
+                "=> 0000: in [15]",
+                "watching cell 15 (currently 0)",
+                "cell 15 changed from 0 to 4",
+                "=> 0002: jf [15], 14",
+                "=> 0005: mul 2, [15], [15]",
+                "0005: mul 2, [15], [15]",
+                "0009: out [15]",
+                "unknown command `bogus`; try `help`",
+                "2 cells from 18446744073709551615 run past the end of memory",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_end_of_memory() {
+        let program = Program::my_parse("1105,1,18446744073709551615").unwrap();
+        let out = session_with(program, "s\ndisasm 3\nq\n");
+        assert_eq!(
+            out,
+            [
+                "=> 0000: jt 1, 18446744073709551615",
+                "=> 18446744073709551615: data 0",
+                "18446744073709551615: data 0",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use itertools::Itertools;
+
+use super::{
+    Val,
+    memory::Memory,
+    opcode::{InstructionMode, Opcode},
+};
+
+/// Words in the longest instruction
+const MAX_LEN: usize = 4;
+
+/// A single operand, displayed with its mode: `[N]`, `N` or `[rb+N]`
+#[derive(Clone, Copy)]
+pub struct Operand {
+    pub mode: InstructionMode,
+    pub val: Val,
+}
+
+impl std::fmt::Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mode {
+            InstructionMode::Parameter => write!(f, "[{}]", self.val),
+            InstructionMode::Immediate => write!(f, "{}", self.val),
+            InstructionMode::Relative if self.val < 0 => write!(f, "[rb-{}]", -self.val),
+            InstructionMode::Relative => write!(f, "[rb+{}]", self.val),
+        }
+    }
+}
+
+/// What sits at an address: an instruction, or a word that can't be decoded as one
+pub enum Item {
+    Instruction {
+        opcode: Opcode,
+        operands: Vec<Operand>,
+    },
+    Data(Val),
+}
+
+impl Item {
+    /// Decode the item at `addr`; an instruction whose operands run off the end is data
+    pub fn decode(code: &[Val], addr: usize) -> Self {
+        let raw = code[addr];
+        let Ok(opcode) = Opcode::new(&raw) else {
+            return Item::Data(raw);
+        };
+        let count = opcode.variant.instruction_count();
+        let Some(params) = code.get(addr + 1..addr + 1 + count) else {
+            return Item::Data(raw);
+        };
+        let operands = params
+            .iter()
+            .enumerate()
+            .map(|(param, &val)| Operand {
+                mode: opcode.mode(param),
+                val,
+            })
+            .collect();
+        Item::Instruction { opcode, operands }
+    }
+
+    /// Decode the item at `addr` in live memory, which is zero rather than ending
+    pub fn decode_memory(memory: &Memory, addr: usize) -> Self {
+        let window = (addr..=addr.saturating_add(MAX_LEN - 1))
+            .map(|addr| memory[addr])
+            .collect_vec();
+        Self::decode(&window, 0)
+    }
+
+    /// Number of words the item takes up
+    pub fn len(&self) -> usize {
+        match self {
+            Item::Instruction { operands, .. } => operands.len() + 1,
+            Item::Data(_) => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Item::Instruction { opcode, operands } if operands.is_empty() => {
+                write!(f, "{}", opcode.variant)
+            }
+            Item::Instruction { opcode, operands } => {
+                write!(f, "{} {}", opcode.variant, operands.iter().join(", "))
+            }
+            Item::Data(val) => write!(f, "data {}", val),
+        }
+    }
+}
+
+/// Decode `code` front to back, one line per item
+pub fn disassemble(code: &[Val]) -> Vec<(usize, Item)> {
+    let mut items = vec![];
+    let mut addr = 0;
+    while addr < code.len() {
+        let item = Item::decode(code, addr);
+        let len = item.len();
+        items.push((addr, item));
+        addr += len;
+    }
+    items
+}
+
+/// Render a listing such as `0004: mul [4], 3, [rb-1]`
+pub fn listing(code: &[Val]) -> String {
+    disassemble(code)
+        .into_iter()
+        .map(|(addr, item)| format!("{:04}: {}", addr, item))
+        .join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::Program;
+
+    #[test]
+    fn test_listing() {
+        let code = Program::parse_code("1002,4,3,4,33,109,-1,21101,7,8,-2,204,1,99,123").unwrap();
+        assert_eq!(
+            listing(&code),
+            [
+                "0000: mul [4], 3, [4]",
+                "0004: data 33",
+                "0005: arb -1",
+                "0007: add 7, 8, [rb-2]",
+                "0011: out [rb+1]",
+                "0013: hlt",
+                "0014: data 123",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_truncated_instruction_is_data() {
+        let code = Program::parse_code("99,1,2").unwrap();
+        assert_eq!(listing(&code), "0000: hlt\n0001: data 1\n0002: data 2");
+    }
+}
@@ -0,0 +1,52 @@
+use super::Val;
+
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum VmErrorKind<W = Val> {
+    #[display("unknown opcode")]
+    UnknownOpcode,
+    #[display("unknown parameter mode {_0}")]
+    BadMode(u8),
+    #[display("parameter {_0} is written to, so it cannot be in immediate mode")]
+    ImmediateWrite(usize),
+    #[display("address {_0} is negative")]
+    NegativeAddress(W),
+    #[display("address {_0} is out of range")]
+    OutOfRange(W),
+    #[display("arithmetic overflow")]
+    Overflow,
+    #[display("no input is available")]
+    InputUnavailable,
+    #[display("output could not be written")]
+    OutputUnavailable,
+    #[display("step limit reached after {_0} steps")]
+    StepLimit(u64),
+    #[display("timed out after {timeout:?} ({steps} steps)")]
+    Timeout {
+        steps: u64,
+        timeout: std::time::Duration,
+    },
+}
+
+impl<W> VmErrorKind<W> {
+    /// Whether the program was stopped by `Limits` rather than failing on its own
+    pub fn is_limit(&self) -> bool {
+        matches!(self, Self::StepLimit(_) | Self::Timeout { .. })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VmError<W = Val> {
+    /// Address of the faulting instruction
+    pub ip: usize,
+    /// The raw instruction at `ip`
+    pub opcode: W,
+    pub kind: VmErrorKind<W>,
+}
+
+impl<W: std::fmt::Display> std::fmt::Display for VmError<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ip {} (opcode {}): {}", self.ip, self.opcode, self.kind)
+    }
+}
+
+impl<W: std::fmt::Debug + std::fmt::Display> std::error::Error for VmError<W> {}
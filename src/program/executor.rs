@@ -0,0 +1,191 @@
+use std::collections::VecDeque;
+
+use super::{
+    Program, Val,
+    error::VmError,
+    machine::{Event, Machine},
+    word::Word,
+};
+
+/// A machine added to an `Executor`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, derive_more::Display)]
+pub struct MachineId(usize);
+
+/// Why `Executor::run` returned
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Idle {
+    /// Every machine halted
+    Halted,
+    /// No machine can make progress; these are waiting for input nobody will send
+    Blocked(Vec<MachineId>),
+}
+
+/// A machine faulted while running under an `Executor`
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+#[display("machine {machine}: {error}")]
+pub struct ExecutorError<W = Val> {
+    pub machine: MachineId,
+    pub error: VmError<W>,
+}
+
+impl<W: std::fmt::Debug + std::fmt::Display> std::error::Error for ExecutorError<W> {}
+
+#[derive(Clone)]
+struct Task<W> {
+    machine: Machine<W>,
+    /// Machines that receive everything this one outputs
+    pipes: Vec<MachineId>,
+    /// Everything this machine has output, if it's being collected
+    collected: Option<Vec<W>>,
+    halted: bool,
+    /// Whether the task is in the run queue
+    queued: bool,
+}
+
+/// Runs many machines cooperatively on the calling thread.
+///
+/// Each machine runs until it blocks on input or halts, then the next runnable one
+/// takes over; output is delivered through pipes added with `pipe`.
+#[derive(Clone, Default)]
+pub struct Executor<W = Val> {
+    tasks: Vec<Task<W>>,
+    run_queue: VecDeque<MachineId>,
+}
+
+impl<W: Word> Executor<W> {
+    pub fn add(&mut self, program: Program<W>) -> MachineId {
+        let id = MachineId(self.tasks.len());
+        self.tasks.push(Task {
+            machine: Machine::new(program),
+            pipes: vec![],
+            collected: None,
+            halted: false,
+            queued: true,
+        });
+        self.run_queue.push_back(id);
+        id
+    }
+
+    pub fn provide_input(&mut self, id: MachineId, val: W) {
+        self.tasks[id.0].machine.provide_input(val);
+        self.wake(id);
+    }
+
+    /// Send everything `from` outputs to `to`; a machine may feed several others
+    pub fn pipe(&mut self, from: MachineId, to: MachineId) {
+        self.tasks[from.0].pipes.push(to);
+    }
+
+    /// Keep a copy of everything `id` outputs from now on, for `outputs`
+    pub fn collect(&mut self, id: MachineId) {
+        self.tasks[id.0].collected.get_or_insert_default();
+    }
+
+    /// Everything `id` has output since `collect` was called on it
+    pub fn outputs(&self, id: MachineId) -> &[W] {
+        self.tasks[id.0].collected.as_deref().unwrap_or_default()
+    }
+
+    pub fn is_halted(&self, id: MachineId) -> bool {
+        self.tasks[id.0].halted
+    }
+
+    fn wake(&mut self, id: MachineId) {
+        let task = &mut self.tasks[id.0];
+        if !task.halted && !task.queued {
+            task.queued = true;
+            self.run_queue.push_back(id);
+        }
+    }
+
+    /// Run machines until none of them can make progress
+    pub fn run(&mut self) -> Result<Idle, ExecutorError<W>> {
+        while let Some(id) = self.run_queue.pop_front() {
+            self.tasks[id.0].queued = false;
+            loop {
+                let task = &mut self.tasks[id.0];
+                let event = task
+                    .machine
+                    .run()
+                    .map_err(|error| ExecutorError { machine: id, error })?;
+                match event {
+                    Event::Output(val) => {
+                        if let Some(collected) = &mut task.collected {
+                            collected.push(val.clone());
+                        }
+                        for to in task.pipes.clone() {
+                            self.provide_input(to, val.clone());
+                        }
+                    }
+                    Event::NeedsInput => break,
+                    Event::Halted => {
+                        task.halted = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        let blocked = (0..self.tasks.len())
+            .map(MachineId)
+            .filter(|&id| !self.is_halted(id))
+            .collect::<Vec<_>>();
+        Ok(if blocked.is_empty() {
+            Idle::Halted
+        } else {
+            Idle::Blocked(blocked)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::asm::assemble;
+
+    #[test]
+    fn test_feedback_loop() {
+        // outputs each input plus one, halting once that reaches 10
+        let program = assemble(
+            "
+            loop:   in [x]
+                    add [x], 1, [x]
+                    out [x]
+                    lt [x], 10, [more]
+                    jt [more], loop
+                    hlt
+            x:      data 0
+            more:   data 0
+            ",
+        )
+        .unwrap();
+        let mut executor = Executor::default();
+        let a = executor.add(program.clone());
+        let b = executor.add(program);
+        executor.pipe(a, b);
+        executor.pipe(b, a);
+        executor.collect(b);
+        executor.provide_input(a, 0);
+
+        assert_eq!(executor.run(), Ok(Idle::Halted));
+        assert_eq!(executor.outputs(b), [2, 4, 6, 8, 10]);
+        assert!(executor.outputs(a).is_empty());
+    }
+
+    #[test]
+    fn test_blocked() {
+        let program = assemble("in [0]\nout [0]\nhlt").unwrap();
+        let mut executor = Executor::default();
+        let a = executor.add(program.clone());
+        let b = executor.add(program.clone());
+        let c = executor.add(program);
+        executor.pipe(a, b);
+        executor.collect(b);
+
+        assert_eq!(executor.run(), Ok(Idle::Blocked(vec![a, b, c])));
+        executor.provide_input(a, 7);
+        assert_eq!(executor.run(), Ok(Idle::Blocked(vec![c])));
+        assert_eq!(executor.outputs(b), [7]);
+        assert!(executor.is_halted(a) && executor.is_halted(b));
+    }
+}
@@ -1,49 +1,65 @@
 pub mod ascii_grid;
 pub mod infallible;
-
-use itertools::Itertools;
+pub mod text;
+pub mod transcript;
 
 use super::Val;
-use std::{io::Write, sync::mpsc};
+use std::{
+    collections::VecDeque,
+    io::{IsTerminal, Write},
+    sync::mpsc,
+};
+
+pub trait TryWriteVal<W = Val> {
+    fn try_write_val(&mut self, val: W) -> Option<()>;
+}
 
-pub struct AsciiStdin;
+pub(crate) trait TryReadVal<W = Val> {
+    fn try_read_val(&mut self) -> Option<W>;
+}
 
-impl TryReadVal for AsciiStdin {
-    fn try_read_val(&mut self) -> Option<Val> {
-        {
-            let mut buf = String::new();
-            print!("Input: ");
-            std::io::stdout().flush().unwrap();
-            std::io::stdin().read_line(&mut buf).unwrap();
-            let chars = buf.trim().chars().collect_vec();
-            assert!(chars.len() == 1);
-            let u8ified = chars[0] as u8;
-            Some(u8ified.into())
-        }
-    }
+/// Separate input and output, used together where a program wants one thing to do both
+pub struct Joint<'a, In, Out> {
+    pub input: &'a mut In,
+    pub output: &'a mut Out,
 }
 
-pub trait TryWriteVal {
-    fn try_write_val(&mut self, val: Val) -> Option<()>;
+impl<W, In: TryReadVal<W>, Out> TryReadVal<W> for Joint<'_, In, Out> {
+    fn try_read_val(&mut self) -> Option<W> {
+        self.input.try_read_val()
+    }
 }
 
-pub(crate) trait TryReadVal {
-    fn try_read_val(&mut self) -> Option<Val>;
+impl<W, In, Out: TryWriteVal<W>> TryWriteVal<W> for Joint<'_, In, Out> {
+    fn try_write_val(&mut self, val: W) -> Option<()> {
+        self.output.try_write_val(val)
+    }
 }
 
-impl TryWriteVal for std::io::Stdout {
-    fn try_write_val(&mut self, val: Val) -> std::option::Option<()> {
+impl<W: std::fmt::Display> TryWriteVal<W> for std::io::Stdout {
+    fn try_write_val(&mut self, val: W) -> std::option::Option<()> {
         writeln!(self, "{}", val).ok()
     }
 }
 
-impl TryReadVal for std::io::Stdin {
-    fn try_read_val(&mut self) -> Option<Val> {
-        let mut buf = String::new();
-        print!("Input: ");
-        std::io::stdout().flush().unwrap();
-        self.read_line(&mut buf).unwrap();
-        Some(buf.trim().parse().unwrap())
+/// One value per line, prompting only when stdin is a terminal; fails at the end of input.
+///
+/// A line that isn't a value is reported on stderr and skipped.
+impl<W: std::str::FromStr> TryReadVal<W> for std::io::Stdin {
+    fn try_read_val(&mut self) -> Option<W> {
+        loop {
+            let mut buf = String::new();
+            if self.is_terminal() {
+                eprint!("Input: ");
+            }
+            if self.read_line(&mut buf).ok()? == 0 {
+                return None;
+            }
+            match buf.trim().parse() {
+                Ok(val) => return Some(val),
+                Err(_) => eprintln!("`{}` is not a valid input; skipping it", buf.trim()),
+            }
+        }
     }
 }
 
@@ -60,9 +76,12 @@ impl TryWriteVal for Val {
     }
 }
 
+/// Input read from a list or iterator of values; reading past the end fails instead of blocking
 pub struct View {
-    pub(crate) view: Vec<Val>,
-    pub(crate) offset: usize,
+    source: Box<dyn Iterator<Item = Val>>,
+    /// Values added after creation, read once `source` runs out
+    queued: VecDeque<Val>,
+    consumed: usize,
 }
 
 impl View {
@@ -70,42 +89,93 @@ impl View {
     where
         <T as std::convert::TryInto<i128>>::Error: std::fmt::Debug,
     {
+        let view: Vec<Val> = view.into_iter().map(|x| x.try_into().unwrap()).collect();
+        Self::stream(view)
+    }
+
+    /// Read values lazily from `source`, e.g. as they're typed in
+    pub fn stream(source: impl IntoIterator<Item = Val> + 'static) -> Self {
         Self {
-            view: view.into_iter().map(|x| x.try_into().unwrap()).collect(),
-            offset: 0,
+            source: Box::new(source.into_iter().fuse()),
+            queued: VecDeque::new(),
+            consumed: 0,
         }
     }
 
-    pub fn new_char(view: Vec<char>) -> Self {
-        let u8s = view.into_iter().map(|x| x as u8).collect_vec();
-        Self::new(u8s)
+    /// Add `val` after everything else; never read if the source is endless
+    pub fn push(&mut self, val: Val) {
+        self.queued.push_back(val);
+    }
+
+    /// Number of values the program has read so far
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+}
+
+impl Extend<Val> for View {
+    fn extend<T: IntoIterator<Item = Val>>(&mut self, iter: T) {
+        for val in iter {
+            self.push(val);
+        }
     }
 }
 
 impl TryReadVal for View {
     fn try_read_val(&mut self) -> Option<Val> {
-        let val = self.view[self.offset];
-        self.offset += 1;
+        let val = self.source.next().or_else(|| self.queued.pop_front())?;
+        self.consumed += 1;
         Some(val)
     }
 }
 
-impl TryReadVal for mpsc::Receiver<Val> {
-    fn try_read_val(&mut self) -> Option<Val> {
+impl<W> TryReadVal<W> for mpsc::Receiver<W> {
+    fn try_read_val(&mut self) -> Option<W> {
         self.recv().ok()
     }
 }
 
-impl TryWriteVal for mpsc::Sender<Val> {
-    fn try_write_val(&mut self, val: Val) -> Option<()> {
-        self.send(val).unwrap();
-        Some(())
+impl<W> TryWriteVal<W> for mpsc::Sender<W> {
+    fn try_write_val(&mut self, val: W) -> Option<()> {
+        self.send(val).ok()
     }
 }
 
-impl TryWriteVal for Vec<Val> {
-    fn try_write_val(&mut self, val: Val) -> Option<()> {
+impl<W> TryWriteVal<W> for Vec<W> {
+    fn try_write_val(&mut self, val: W) -> Option<()> {
         self.push(val);
         Some(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_view_exhaustion() {
+        let mut view = View::new(vec![1, 2]);
+        assert_eq!(view.try_read_val(), Some(1));
+        view.extend([3]);
+        assert_eq!(view.try_read_val(), Some(2));
+        assert_eq!(view.try_read_val(), Some(3));
+        assert_eq!(view.try_read_val(), None);
+        assert_eq!(view.consumed(), 3);
+
+        let mut view = View::stream((10..).step_by(10));
+        assert_eq!(view.try_read_val(), Some(10));
+        assert_eq!(view.try_read_val(), Some(20));
+        assert_eq!(view.consumed(), 2);
+
+        // a source that comes back after running out isn't read again
+        let mut calls = 0;
+        let mut view = View::stream(std::iter::from_fn(move || {
+            calls += 1;
+            (calls != 2).then_some(calls)
+        }));
+        view.push(7);
+        assert_eq!(view.try_read_val(), Some(1));
+        assert_eq!(view.try_read_val(), Some(7));
+        assert_eq!(view.try_read_val(), None);
+    }
+}
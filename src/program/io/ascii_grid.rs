@@ -1,11 +1,12 @@
 use crate::program::Val;
 
+/// Collects printed ASCII into rows; a value that isn't ASCII can't be written
 impl<C: From<char>> crate::program::io::TryWriteVal for Vec<Vec<C>> {
     fn try_write_val(&mut self, val: Val) -> Option<()> {
         if self.is_empty() {
             self.push(vec![]);
         }
-        let c: char = (u8::try_from(val).unwrap()).into();
+        let c: char = u8::try_from(val).ok().filter(u8::is_ascii)?.into();
         match c {
             '\n' => {
                 self.push(vec![]);
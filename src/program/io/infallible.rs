@@ -1,15 +1,16 @@
 use super::{TryReadVal, TryWriteVal};
 
+/// I/O for programs that never read or write; any attempt fails the program
 pub struct Unused;
 
-impl TryReadVal for Unused {
-    fn try_read_val(&mut self) -> Option<crate::program::Val> {
-        unreachable!("Shouldn't try to read a value!");
+impl<W> TryReadVal<W> for Unused {
+    fn try_read_val(&mut self) -> Option<W> {
+        None
     }
 }
 
-impl TryWriteVal for Unused {
-    fn try_write_val(&mut self, _val: crate::program::Val) -> Option<()> {
-        unreachable!("Shouldn't try to write a value!");
+impl<W> TryWriteVal<W> for Unused {
+    fn try_write_val(&mut self, _val: W) -> Option<()> {
+        None
     }
 }
@@ -0,0 +1,208 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Write},
+};
+
+use anyhow::Context;
+
+use super::{TryReadVal, TryWriteVal};
+use crate::program::Val;
+
+/// How values are spelled out as text
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// One number per line
+    Lines,
+    /// Numbers separated by commas; when reading, any mix of commas and whitespace
+    Commas,
+    /// Each value is a character; output that isn't ASCII is printed as a number on its own line
+    Ascii,
+}
+
+/// Reads values from text a line at a time, optionally prompting for each line
+pub struct TextInput<R> {
+    reader: R,
+    format: Format,
+    prompt: bool,
+    /// Values from the last line that haven't been read yet
+    pending: VecDeque<Val>,
+    /// Why reading stopped early, if it was something other than running out of input
+    error: Option<anyhow::Error>,
+}
+
+impl<R: BufRead> TextInput<R> {
+    pub fn new(reader: R, format: Format) -> Self {
+        Self {
+            reader,
+            format,
+            prompt: false,
+            pending: VecDeque::new(),
+            error: None,
+        }
+    }
+
+    /// Print `Input: ` on stderr before reading each line of numbers
+    pub fn prompt(self, prompt: bool) -> Self {
+        Self { prompt, ..self }
+    }
+
+    /// Fail if reading stopped on bad input rather than at the end of it
+    pub fn finish(self) -> anyhow::Result<()> {
+        self.error.map_or(Ok(()), Err)
+    }
+
+    fn read_line(&mut self) -> anyhow::Result<bool> {
+        if self.prompt && self.format != Format::Ascii {
+            eprint!("Input: ");
+        }
+        let mut line = String::new();
+        if self
+            .reader
+            .read_line(&mut line)
+            .context("could not read input")?
+            == 0
+        {
+            return Ok(false);
+        }
+        match self.format {
+            Format::Lines | Format::Commas => {
+                for word in line.split([',', ' ', '\t', '\r', '\n']) {
+                    if !word.is_empty() {
+                        let val = word
+                            .parse()
+                            .with_context(|| format!("`{}` is not a valid input", word))?;
+                        self.pending.push_back(val);
+                    }
+                }
+            }
+            Format::Ascii => {
+                anyhow::ensure!(line.is_ascii(), "`{}` is not ASCII", line.trim_end());
+                self.pending.extend(line.bytes().map(Val::from));
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> TryReadVal for TextInput<R> {
+    fn try_read_val(&mut self) -> Option<Val> {
+        while self.pending.is_empty() && self.error.is_none() {
+            match self.read_line() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(err) => self.error = Some(err),
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Writes values as text, flushing after each so a reader on the other end of a pipe sees them
+pub struct TextOutput<W: Write> {
+    writer: W,
+    format: Format,
+    /// Whether the current line has anything on it
+    mid_line: bool,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> TextOutput<W> {
+    pub fn new(writer: W, format: Format) -> Self {
+        Self {
+            writer,
+            format,
+            mid_line: false,
+            error: None,
+        }
+    }
+
+    /// End the last line and report any error from writing
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        if let Some(err) = self.error {
+            return Err(err).context("could not write output");
+        }
+        if self.mid_line {
+            writeln!(self.writer).context("could not write output")?;
+        }
+        self.writer.flush().context("could not write output")
+    }
+
+    fn write(&mut self, val: Val) -> std::io::Result<()> {
+        match self.format {
+            Format::Lines => writeln!(self.writer, "{}", val)?,
+            Format::Commas => {
+                if self.mid_line {
+                    write!(self.writer, ",")?;
+                }
+                write!(self.writer, "{}", val)?;
+                self.mid_line = true;
+            }
+            Format::Ascii => match u8::try_from(val).ok().filter(u8::is_ascii) {
+                Some(c) => {
+                    self.writer.write_all(&[c])?;
+                    self.mid_line = c != b'\n';
+                }
+                None => {
+                    if self.mid_line {
+                        writeln!(self.writer)?;
+                    }
+                    writeln!(self.writer, "{}", val)?;
+                    self.mid_line = false;
+                }
+            },
+        }
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> TryWriteVal for TextOutput<W> {
+    fn try_write_val(&mut self, val: Val) -> Option<()> {
+        if self.error.is_some() {
+            return None;
+        }
+        let result = self.write(val);
+        result.map_err(|err| self.error = Some(err)).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn round_trip(input: &str, in_format: Format, out_format: Format) -> String {
+        let mut reader = TextInput::new(input.as_bytes(), in_format);
+        let mut output = vec![];
+        let mut writer = TextOutput::new(&mut output, out_format);
+        while let Some(val) = reader.try_read_val() {
+            writer.try_write_val(val).unwrap();
+        }
+        reader.finish().unwrap();
+        writer.finish().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(
+            round_trip("1, 2\n-3\n", Format::Commas, Format::Lines),
+            "1\n2\n-3\n"
+        );
+        assert_eq!(round_trip("1\n2\n", Format::Lines, Format::Commas), "1,2\n");
+        assert_eq!(
+            round_trip("hi\n", Format::Ascii, Format::Commas),
+            "104,105,10\n"
+        );
+        assert_eq!(
+            round_trip("104,105,1000", Format::Commas, Format::Ascii),
+            "hi\n1000\n"
+        );
+
+        let mut reader = TextInput::new("4\nfive\n".as_bytes(), Format::Lines);
+        assert_eq!(reader.try_read_val(), Some(4));
+        assert_eq!(reader.try_read_val(), None);
+        assert_eq!(
+            reader.finish().unwrap_err().to_string(),
+            "`five` is not a valid input"
+        );
+    }
+}
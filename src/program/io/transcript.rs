@@ -0,0 +1,241 @@
+use std::{collections::VecDeque, io::Write, str::FromStr};
+
+use anyhow::Context;
+use itertools::Itertools;
+
+use super::{TryReadVal, TryWriteVal};
+use crate::{program::Val, utls::parse_lines};
+
+/// One value that crossed between a program and its I/O
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Entry {
+    In(Val),
+    Out(Val),
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Entry::In(val) => write!(f, "in {}", val),
+            Entry::Out(val) => write!(f, "out {}", val),
+        }
+    }
+}
+
+/// Every value a program read or wrote, in order; one `in N` or `out N` per line
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Transcript(pub Vec<Entry>);
+
+impl std::fmt::Display for Transcript {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.iter().join("\n"))
+    }
+}
+
+impl FromStr for Transcript {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let entries = parse_lines(s, |line| {
+            let (dir, val) = line
+                .split_once(' ')
+                .ok_or_else(|| anyhow::anyhow!("expected `in <value>` or `out <value>`"))?;
+            let val = val.trim().parse()?;
+            match dir {
+                "in" => Ok(Entry::In(val)),
+                "out" => Ok(Entry::Out(val)),
+                dir => anyhow::bail!("unknown direction `{}`", dir),
+            }
+        })?;
+        Ok(Self(entries))
+    }
+}
+
+/// Passes values between a program and `io`, writing each to a transcript as it goes by.
+///
+/// Every entry is flushed straight away, so a run that panics or is killed part way
+/// still leaves a transcript of everything up to that point. If the transcript can't be
+/// written the program is stopped, and `finish` reports why.
+pub struct Recording<Io, L: Write> {
+    io: Io,
+    log: L,
+    error: Option<std::io::Error>,
+}
+
+impl<Io, L: Write> Recording<Io, L> {
+    pub fn new(io: Io, log: L) -> Self {
+        Self {
+            io,
+            log,
+            error: None,
+        }
+    }
+
+    /// Fail if the transcript couldn't be written, otherwise hand back the wrapped I/O
+    pub fn finish(self) -> anyhow::Result<Io> {
+        match self.error {
+            Some(err) => Err(err).context("could not write transcript"),
+            None => Ok(self.io),
+        }
+    }
+
+    fn record(&mut self, entry: Entry) -> Option<()> {
+        if self.error.is_some() {
+            return None;
+        }
+        let result = writeln!(self.log, "{}", entry).and_then(|_| self.log.flush());
+        result.map_err(|err| self.error = Some(err)).ok()
+    }
+}
+
+impl<Io: TryReadVal, L: Write> TryReadVal for Recording<Io, L> {
+    fn try_read_val(&mut self) -> Option<Val> {
+        let val = self.io.try_read_val()?;
+        self.record(Entry::In(val))?;
+        Some(val)
+    }
+}
+
+impl<Io: TryWriteVal, L: Write> TryWriteVal for Recording<Io, L> {
+    fn try_write_val(&mut self, val: Val) -> Option<()> {
+        self.record(Entry::Out(val))?;
+        self.io.try_write_val(val)
+    }
+}
+
+/// Plays a transcript back to a program: supplies its inputs and checks the program's
+/// outputs against it. The first mismatch stops the program, and `finish` reports it.
+pub struct Replay {
+    entries: VecDeque<Entry>,
+    /// Entries matched so far
+    position: usize,
+    mismatch: Option<String>,
+}
+
+impl Replay {
+    pub fn new(transcript: Transcript) -> Self {
+        Self {
+            entries: transcript.0.into(),
+            position: 0,
+            mismatch: None,
+        }
+    }
+
+    /// Fail unless the whole transcript was replayed without a mismatch
+    pub fn finish(self) -> anyhow::Result<()> {
+        if let Some(mismatch) = self.mismatch {
+            anyhow::bail!("entry {}: {}", self.position + 1, mismatch);
+        }
+        if let Some(entry) = self.entries.front() {
+            anyhow::bail!(
+                "entry {}: program stopped before `{}`",
+                self.position + 1,
+                entry
+            );
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, actual: Entry) -> Option<Val> {
+        if self.mismatch.is_some() {
+            return None;
+        }
+        let expected = self.entries.front().copied();
+        let val = match (expected, actual) {
+            (Some(Entry::In(val)), Entry::In(_)) => val,
+            (Some(Entry::Out(expected)), Entry::Out(val)) if expected == val => val,
+            (Some(expected), Entry::In(_)) => {
+                self.mismatch = Some(format!("expected `{}`, program read input", expected));
+                return None;
+            }
+            (Some(expected), actual) => {
+                self.mismatch = Some(format!("expected `{}`, found `{}`", expected, actual));
+                return None;
+            }
+            (None, Entry::In(_)) => {
+                self.mismatch = Some("program read past the end of the transcript".to_owned());
+                return None;
+            }
+            (None, actual) => {
+                self.mismatch = Some(format!("transcript ended, found `{}`", actual));
+                return None;
+            }
+        };
+        self.entries.pop_front();
+        self.position += 1;
+        Some(val)
+    }
+}
+
+impl TryReadVal for Replay {
+    fn try_read_val(&mut self) -> Option<Val> {
+        self.expect(Entry::In(0))
+    }
+}
+
+impl TryWriteVal for Replay {
+    fn try_write_val(&mut self, val: Val) -> Option<()> {
+        self.expect(Entry::Out(val)).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        program::{
+            Program,
+            io::{Joint, View},
+            limits::Limits,
+        },
+        utls::MyParse,
+    };
+
+    #[test]
+    fn test_record_and_replay() {
+        // outputs 1 if the input is 8, otherwise 0
+        let mut program: Program = Program::my_parse("3,9,8,9,10,9,4,9,99,-1,8").unwrap();
+        let record = |log: &mut [u8]| -> anyhow::Result<_> {
+            let mut input = View::new(vec![8]);
+            let mut output: Vec<Val> = vec![];
+            let io = Joint {
+                input: &mut input,
+                output: &mut output,
+            };
+            let mut recording = Recording::new(io, log);
+            let result = program
+                .clone()
+                .eval_joint_limited(&mut recording, Limits::default());
+            recording.finish()?;
+            Ok((result, output))
+        };
+
+        let mut log = [0; 64];
+        let (result, output) = record(&mut log).unwrap();
+        result.unwrap();
+        assert_eq!(output, vec![1]);
+        let transcript = String::from_utf8_lossy(&log);
+        let transcript = transcript.trim_end_matches('\0');
+        assert_eq!(transcript, "in 8\nout 1\n");
+
+        // a transcript that can't be written stops the program
+        let err = record(&mut [0; 4]).err().unwrap();
+        assert_eq!(err.to_string(), "could not write transcript");
+
+        let mut replay = Replay::new(transcript.parse().unwrap());
+        program
+            .clone()
+            .eval_joint_limited(&mut replay, Limits::default())
+            .unwrap();
+        replay.finish().unwrap();
+
+        let mut replay = Replay::new("in 7\nout 1".parse().unwrap());
+        program
+            .eval_joint_limited(&mut replay, Limits::default())
+            .unwrap_err();
+        assert_eq!(
+            replay.finish().unwrap_err().to_string(),
+            "entry 2: expected `out 1`, found `out 0`"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::{Duration, Instant};
+
+use super::error::VmErrorKind;
+
+/// Only check the clock every this many steps, since it's much slower than a step
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// Bounds on how long a program may run; unlimited by default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Limits {
+    pub max_steps: Option<u64>,
+    pub timeout: Option<Duration>,
+}
+
+impl Limits {
+    pub fn steps(max_steps: u64) -> Self {
+        Self {
+            max_steps: Some(max_steps),
+            ..Self::default()
+        }
+    }
+}
+
+/// Tracks a single run against its `Limits`
+#[derive(Clone)]
+pub(super) struct Budget {
+    limits: Limits,
+    started: Instant,
+    steps: u64,
+}
+
+impl Budget {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            started: Instant::now(),
+            steps: 0,
+        }
+    }
+
+    /// Account for the next step, failing if it would go over budget
+    pub fn tick<W>(&mut self) -> Result<(), VmErrorKind<W>> {
+        if self
+            .limits
+            .max_steps
+            .is_some_and(|max_steps| self.steps >= max_steps)
+        {
+            return Err(VmErrorKind::StepLimit(self.steps));
+        }
+        if let Some(timeout) = self.limits.timeout
+            && self.steps.is_multiple_of(CLOCK_INTERVAL)
+            && self.started.elapsed() > timeout
+        {
+            return Err(VmErrorKind::Timeout {
+                steps: self.steps,
+                timeout,
+            });
+        }
+        self.steps += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        Program,
+        io::infallible::Unused,
+        machine::Machine,
+        spawn::{SpawnError, spawn_limited},
+    };
+    use crate::utls::MyParse;
+
+    #[test]
+    fn test_limits() {
+        // jumps back to itself forever
+        let program: Program = Program::my_parse("1105,1,0").unwrap();
+        let err = program
+            .clone()
+            .eval_limited(&mut Unused, &mut Unused, Limits::steps(50))
+            .unwrap_err();
+        assert_eq!(err.kind, VmErrorKind::StepLimit(50));
+        let mut machine = Machine::new(program.clone()).with_limits(Limits::steps(50));
+        assert_eq!(machine.run().unwrap_err().kind, VmErrorKind::StepLimit(50));
+
+        let timeout = Duration::from_millis(10);
+        let limits = Limits {
+            timeout: Some(timeout),
+            ..Limits::default()
+        };
+        let Err(SpawnError::Vm(err)) = spawn_limited(program, limits).join() else {
+            panic!("spawned program should time out");
+        };
+        assert!(matches!(err.kind, VmErrorKind::Timeout { steps, .. } if steps > 0));
+    }
+}
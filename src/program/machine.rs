@@ -0,0 +1,181 @@
+use std::collections::VecDeque;
+
+use super::{
+    Program, ProgramState, StepOutcome, Val,
+    error::VmError,
+    io::{TryReadVal, TryWriteVal},
+    limits::{Budget, Limits},
+    memory::Memory,
+    snapshot::Snapshot,
+    trace::{NoTrace, Tracer},
+    word::Word,
+};
+
+/// Why `Machine::run` stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event<W = Val> {
+    /// The program wants input; call `provide_input` and run again
+    NeedsInput,
+    Output(W),
+    Halted,
+}
+
+/// A program that runs on the caller's thread, pausing whenever it reads or writes
+#[derive(Clone)]
+pub struct Machine<W = Val> {
+    program: Program<W>,
+    state: ProgramState<W>,
+    input: VecDeque<W>,
+    budget: Budget,
+}
+
+/// Reads from the machine's queued input and holds on to a single output
+struct Pending<'a, W> {
+    input: &'a mut VecDeque<W>,
+    output: Option<W>,
+}
+
+impl<W> TryReadVal<W> for Pending<'_, W> {
+    fn try_read_val(&mut self) -> Option<W> {
+        self.input.pop_front()
+    }
+}
+
+impl<W> TryWriteVal<W> for Pending<'_, W> {
+    fn try_write_val(&mut self, val: W) -> Option<()> {
+        self.output = Some(val);
+        Some(())
+    }
+}
+
+impl<W: Word> Machine<W> {
+    pub fn new(program: Program<W>) -> Self {
+        Self {
+            program,
+            state: ProgramState::default(),
+            input: VecDeque::new(),
+            budget: Budget::new(Limits::default()),
+        }
+    }
+
+    /// Stop with an error once `limits` are exceeded, counting from now
+    pub fn with_limits(self, limits: Limits) -> Self {
+        Self {
+            budget: Budget::new(limits),
+            ..self
+        }
+    }
+
+    pub fn provide_input(&mut self, val: W) {
+        self.input.push_back(val);
+    }
+
+    /// Number of input values queued but not yet read
+    pub fn pending_input(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Address of the next instruction
+    pub fn ip(&self) -> usize {
+        self.state.offset
+    }
+
+    pub fn relative_base(&self) -> &W {
+        &self.state.relative_base
+    }
+
+    pub fn memory(&self) -> &Memory<W> {
+        &self.program.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut Memory<W> {
+        &mut self.program.memory
+    }
+
+    pub fn snapshot(&self) -> Snapshot<W> {
+        Snapshot {
+            memory: self.program.memory.clone(),
+            state: self.state.clone(),
+            input: self.input.iter().cloned().collect(),
+        }
+    }
+
+    /// Continue from `snapshot`, discarding the current state
+    pub fn restore(&mut self, snapshot: &Snapshot<W>) {
+        self.program.memory.clone_from(&snapshot.memory);
+        self.state = snapshot.state.clone();
+        self.input = snapshot.input.iter().cloned().collect();
+    }
+
+    /// Run until the program produces output, runs out of input or halts
+    pub fn run(&mut self) -> Result<Event<W>, VmError<W>> {
+        loop {
+            if let Some(event) = self.step()? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Execute a single instruction, returning the event it caused, if any
+    pub fn step(&mut self) -> Result<Option<Event<W>>, VmError<W>> {
+        self.step_traced(&mut NoTrace)
+    }
+
+    /// `step`, reporting the instruction to `tracer` if it executes
+    pub fn step_traced(
+        &mut self,
+        tracer: &mut impl Tracer<W>,
+    ) -> Result<Option<Event<W>>, VmError<W>> {
+        if let Err(kind) = self.budget.tick() {
+            return Err(self.program.fault(self.state.offset, kind));
+        }
+        let mut io = Pending {
+            input: &mut self.input,
+            output: None,
+        };
+        let outcome = self.program.step_traced(&mut self.state, &mut io, tracer)?;
+        Ok(match (outcome, io.output) {
+            (_, Some(val)) => Some(Event::Output(val)),
+            (StepOutcome::Continue, None) => None,
+            (StepOutcome::Halted, None) => Some(Event::Halted),
+            (StepOutcome::NeedsInput, None) => Some(Event::NeedsInput),
+            (StepOutcome::OutputBlocked, None) => unreachable!("machine output never blocks"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::asm::assemble;
+
+    #[test]
+    fn test_resume() {
+        let program = assemble(
+            "
+            # doubles each input until it reads a zero
+            loop:   in [x]
+                    jf [x], end
+                    mul 2, [x], [x]
+                    out [x]
+                    jt 1, loop
+            end:    hlt
+            x:      data 0
+            ",
+        )
+        .unwrap();
+        let mut machine = Machine::new(program);
+        assert_eq!(machine.run(), Ok(Event::NeedsInput));
+        assert_eq!(machine.run(), Ok(Event::NeedsInput));
+
+        machine.provide_input(3);
+        machine.provide_input(5);
+        assert_eq!(machine.run(), Ok(Event::Output(6)));
+        assert_eq!(machine.run(), Ok(Event::Output(10)));
+        assert_eq!(machine.run(), Ok(Event::NeedsInput));
+
+        machine.provide_input(0);
+        assert_eq!(machine.run(), Ok(Event::Halted));
+        assert_eq!(machine.run(), Ok(Event::Halted));
+    }
+}
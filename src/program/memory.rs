@@ -0,0 +1,117 @@
+use std::{
+    collections::HashMap,
+    ops::{Index, IndexMut},
+};
+
+use itertools::Itertools;
+
+use super::{Val, word::Word};
+
+/// Writes this far past the end of dense memory extend it; anything further is stored sparsely
+const DENSE_SLACK: usize = 4096;
+
+/// Intcode memory, where every address starts out as zero.
+///
+/// The program image and nearby writes live in a `Vec`, so clones of a freshly
+/// loaded program only copy the image itself.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct Memory<W = Val> {
+    dense: Vec<W>,
+    sparse: HashMap<usize, W>,
+    /// What unallocated addresses read as
+    zero: W,
+}
+
+impl<W: Word> Memory<W> {
+    pub fn new(code: Vec<W>) -> Self {
+        Self::from_parts(code, HashMap::new())
+    }
+
+    /// Build memory from its dense image and the cells stored beyond it
+    pub(super) fn from_parts(dense: Vec<W>, sparse: HashMap<usize, W>) -> Self {
+        Self {
+            dense,
+            sparse,
+            zero: W::zero(),
+        }
+    }
+
+    /// The program image and the cells written near it
+    pub fn dense(&self) -> &[W] {
+        &self.dense
+    }
+
+    /// Cells stored outside the dense image, by address
+    pub fn sparse(&self) -> impl Iterator<Item = (usize, W)> {
+        self.sparse
+            .iter()
+            .map(|(&addr, val)| (addr, val.clone()))
+            .sorted()
+    }
+
+    /// Mutable access to `addr`, allocating it if needed
+    pub fn get_mut(&mut self, addr: usize) -> &mut W {
+        if addr < self.dense.len() {
+            return &mut self.dense[addr];
+        }
+        if addr < self.dense.len() + DENSE_SLACK {
+            // pull in anything sparse that the dense part now covers
+            let start = self.dense.len();
+            self.dense.resize(addr + 1, W::zero());
+            if !self.sparse.is_empty() {
+                for (i, val) in self.dense[start..].iter_mut().enumerate() {
+                    if let Some(stored) = self.sparse.remove(&(start + i)) {
+                        *val = stored;
+                    }
+                }
+            }
+            return &mut self.dense[addr];
+        }
+        self.sparse.entry(addr).or_insert_with(W::zero)
+    }
+}
+
+impl<W> Index<usize> for Memory<W> {
+    type Output = W;
+
+    fn index(&self, addr: usize) -> &W {
+        self.dense
+            .get(addr)
+            .or_else(|| self.sparse.get(&addr))
+            .unwrap_or(&self.zero)
+    }
+}
+
+impl<W: Word> IndexMut<usize> for Memory<W> {
+    fn index_mut(&mut self, addr: usize) -> &mut W {
+        self.get_mut(addr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_grows_on_demand() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        assert_eq!(memory[1], 2);
+        assert_eq!(memory[1_000_000_000], 0);
+
+        memory[10] = 4;
+        memory[1_000_000_000] = 5;
+        assert_eq!(memory.dense.len(), 11);
+        assert_eq!(memory.sparse.len(), 1);
+        assert_eq!((memory[9], memory[10]), (0, 4));
+        assert_eq!(memory[1_000_000_000], 5);
+
+        // growing over a sparse address keeps its value
+        memory[DENSE_SLACK + 11] = 6;
+        assert_eq!(memory.sparse.len(), 2);
+        memory[DENSE_SLACK + 10] = 7;
+        memory[DENSE_SLACK + 12] = 8;
+        assert_eq!(memory.dense.len(), DENSE_SLACK + 13);
+        assert_eq!(memory[DENSE_SLACK + 11], 6);
+        assert_eq!(memory.sparse.len(), 1);
+    }
+}
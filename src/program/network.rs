@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use itertools::Itertools;
+
+use super::{
+    Program, Val,
+    executor::{Executor, Idle, MachineId},
+    word::Word,
+};
+
+/// A set of named machines wired together with pipes, built up declaratively:
+///
+/// ```text
+/// network.node("a", program.clone()).node("b", program).input("a", [0]);
+/// network.pipe("a", "b").pipe("b", "a").collect("b");
+/// let outputs = network.run()?;
+/// ```
+///
+/// Each node runs its own copy of its program. Pipes deliver every output of one node
+/// as input to another, so loops and fan-out are just more pipes.
+#[derive(Clone, Default)]
+pub struct Network<W = Val> {
+    nodes: Vec<(String, Program<W>)>,
+    inputs: Vec<(String, W)>,
+    pipes: Vec<(String, String)>,
+    collected: Vec<String>,
+}
+
+impl<W: Word> Network<W> {
+    pub fn node(&mut self, name: &str, program: Program<W>) -> &mut Self {
+        self.nodes.push((name.to_owned(), program));
+        self
+    }
+
+    /// Queue `vals` as input to `name`, ahead of anything piped to it
+    pub fn input(&mut self, name: &str, vals: impl IntoIterator<Item = W>) -> &mut Self {
+        self.inputs
+            .extend(vals.into_iter().map(|val| (name.to_owned(), val)));
+        self
+    }
+
+    pub fn pipe(&mut self, from: &str, to: &str) -> &mut Self {
+        self.pipes.push((from.to_owned(), to.to_owned()));
+        self
+    }
+
+    /// Include everything `name` outputs in the result of `run`
+    pub fn collect(&mut self, name: &str) -> &mut Self {
+        self.collected.push(name.to_owned());
+        self
+    }
+
+    /// Run every node until all of them halt, returning the outputs of collected nodes.
+    ///
+    /// Fails if a node faults, or if the nodes that are still running are all waiting
+    /// for input.
+    pub fn run(&self) -> anyhow::Result<HashMap<String, Vec<W>>> {
+        let mut executor = Executor::default();
+        let mut ids = HashMap::new();
+        for (name, program) in &self.nodes {
+            let id = executor.add(program.clone());
+            anyhow::ensure!(
+                ids.insert(name.as_str(), id).is_none(),
+                "node `{}` is defined more than once",
+                name
+            );
+        }
+        let id = |name: &str| -> anyhow::Result<MachineId> {
+            ids.get(name)
+                .copied()
+                .with_context(|| format!("no node named `{}`", name))
+        };
+
+        for (name, val) in &self.inputs {
+            executor.provide_input(id(name)?, val.clone());
+        }
+        for (from, to) in &self.pipes {
+            executor.pipe(id(from)?, id(to)?);
+        }
+        for name in &self.collected {
+            executor.collect(id(name)?);
+        }
+
+        let names: HashMap<MachineId, &str> = ids.iter().map(|(&name, &id)| (id, name)).collect();
+        if let Idle::Blocked(blocked) = executor.run()? {
+            anyhow::bail!(
+                "deadlock: {} waiting for input",
+                blocked.iter().map(|id| names[id]).join(", ")
+            );
+        }
+        self.collected
+            .iter()
+            .map(|name| Ok((name.clone(), executor.outputs(id(name)?).to_vec())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::asm::assemble;
+
+    #[test]
+    fn test_fan_out() {
+        // doubles its input once
+        let double = assemble("in [x]\nmul [x], 2, [x]\nout [x]\nhlt\nx: data 0").unwrap();
+        let mut network = Network::default();
+        network
+            .node("src", double.clone())
+            .node("left", double.clone())
+            .node("right", double)
+            .input("src", [3])
+            .pipe("src", "left")
+            .pipe("src", "right")
+            .collect("left")
+            .collect("right");
+        let outputs = network.run().unwrap();
+        assert_eq!(outputs["left"], [12]);
+        assert_eq!(outputs["right"], [12]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let echo = assemble("loop: in [x]\nout [x]\njt 1, loop\nx: data 0").unwrap();
+        let mut network = Network::default();
+        network
+            .node("a", echo.clone())
+            .node("b", echo)
+            .pipe("a", "b")
+            .input("a", [1]);
+        let err = network.run().unwrap_err();
+        assert_eq!(err.to_string(), "deadlock: a, b waiting for input");
+
+        network.pipe("a", "c");
+        let err = network.run().unwrap_err();
+        assert_eq!(err.to_string(), "no node named `c`");
+    }
+}
@@ -1,22 +1,36 @@
 use super::{
-    Program, ProgramState,
+    Program, ProgramState, StepOutcome,
+    error::VmErrorKind,
     io::{TryReadVal, TryWriteVal},
+    trace::{TraceEvent, Tracer},
+    word::Word,
 };
-use crate::program::Val;
 use num_enum::TryFromPrimitive;
+use strum_macros::EnumIter;
 
-#[derive(TryFromPrimitive)]
+/// Displays as the disassembler mnemonic
+#[derive(TryFromPrimitive, Clone, Copy, EnumIter, derive_more::Display)]
 #[repr(i32)]
-enum OpcodeVariant {
+pub(super) enum OpcodeVariant {
+    #[display("add")]
     Add = 1,
+    #[display("mul")]
     Mult = 2,
+    #[display("in")]
     Input = 3,
+    #[display("out")]
     Output = 4,
+    #[display("jt")]
     JumpIfTrue = 5,
+    #[display("jf")]
     JumpIfFalse = 6,
+    #[display("lt")]
     LessThan = 7,
+    #[display("eq")]
     Equals = 8,
+    #[display("arb")]
     RelativeBaseOffset = 9,
+    #[display("hlt")]
     Halt = 99,
 }
 
@@ -35,6 +49,18 @@ impl OpcodeVariant {
             OpcodeVariant::RelativeBaseOffset => 1,
         }
     }
+
+    /// The parameter the instruction stores its result through, if any
+    pub fn write_param(&self) -> Option<usize> {
+        match self {
+            OpcodeVariant::Add
+            | OpcodeVariant::Mult
+            | OpcodeVariant::LessThan
+            | OpcodeVariant::Equals => Some(2),
+            OpcodeVariant::Input => Some(0),
+            _ => None,
+        }
+    }
 }
 
 #[derive(TryFromPrimitive, Default, Clone, Copy)]
@@ -46,140 +72,252 @@ pub enum InstructionMode {
     Relative = 2,
 }
 
+/// Parameters taken by the widest instruction
+const MAX_PARAMS: usize = 3;
+
+#[derive(Clone, Copy)]
 pub struct Opcode {
-    variant: OpcodeVariant,
-    instrs: Vec<InstructionMode>,
+    pub(super) variant: OpcodeVariant,
+    modes: [InstructionMode; MAX_PARAMS],
+}
+
+/// Decoded instructions by address, for programs that run the same code over and over.
+///
+/// Each entry remembers the word it was decoded from and is only used while memory
+/// still holds that word, so self-modifying writes invalidate it.
+#[derive(Clone, Default)]
+pub(super) struct DecodeCache<W> {
+    entries: Vec<Option<(W, Opcode)>>,
+}
+
+impl<W: Word> DecodeCache<W> {
+    pub fn decode(&mut self, addr: usize, instr_raw: &W) -> Result<Opcode, VmErrorKind<W>> {
+        if let Some(Some((raw, opcode))) = self.entries.get(addr)
+            && raw == instr_raw
+        {
+            return Ok(*opcode);
+        }
+        let opcode = Opcode::new(instr_raw)?;
+        if addr >= self.entries.len() {
+            self.entries.resize(addr + 1, None);
+        }
+        self.entries[addr] = Some((instr_raw.clone(), opcode));
+        Ok(opcode)
+    }
+}
+
+fn address<W: Word>(loc: &W) -> Result<usize, VmErrorKind<W>> {
+    if loc.is_negative() {
+        return Err(VmErrorKind::NegativeAddress(loc.clone()));
+    }
+    loc.to_usize()
+        .ok_or_else(|| VmErrorKind::OutOfRange(loc.clone()))
+}
+
+/// `base + by` as an address, failing if it's past the end of the address space
+fn offset_address<W: Word>(base: usize, by: usize) -> Result<usize, VmErrorKind<W>> {
+    base.checked_add(by).ok_or_else(|| {
+        W::from_usize(base)
+            .zip(W::from_usize(by))
+            .and_then(|(base, by)| base.checked_add(&by))
+            .map_or(VmErrorKind::Overflow, VmErrorKind::OutOfRange)
+    })
 }
 
 impl Opcode {
-    pub(crate) fn eval<Io: TryReadVal + TryWriteVal>(
+    /// Evaluate the opcode at `program_state`, advancing it unless the opcode halted or blocked
+    pub(crate) fn eval<W: Word, Io: TryReadVal<W> + TryWriteVal<W>, T: Tracer<W>>(
         &self,
-        program: &mut Program,
-        program_state: ProgramState,
+        program: &mut Program<W>,
+        program_state: &mut ProgramState<W>,
         io: &mut Io,
-    ) -> Option<ProgramState> {
-        struct EvalCtx<'a> {
-            program_state: ProgramState,
-            instrs: &'a [InstructionMode],
-            program: &'a mut Program,
+        tracer: &mut T,
+    ) -> Result<StepOutcome, VmErrorKind<W>> {
+        struct EvalCtx<'a, W> {
+            program_state: &'a mut ProgramState<W>,
+            opcode: &'a Opcode,
+            program: &'a mut Program<W>,
+            /// Only filled in when `trace` is set
+            event: TraceEvent<W>,
+            trace: bool,
         }
 
-        impl EvalCtx<'_> {
-            fn immediate(&mut self, param: usize) -> &mut Val {
-                &mut self.program.code[self.program_state.offset + param + 1]
+        impl<W: Word> EvalCtx<'_, W> {
+            fn immediate(&self, param: usize) -> Result<&W, VmErrorKind<W>> {
+                let addr = offset_address(self.program_state.offset, param + 1)?;
+                Ok(&self.program.memory[addr])
             }
 
-            fn lookup(&mut self, loc: usize) -> &mut Val {
-                &mut self.program.code[loc]
+            /// The address parameter <offset> refers to, in a non-immediate mode
+            fn address(&self, param: usize) -> Result<usize, VmErrorKind<W>> {
+                match self.mode(param) {
+                    InstructionMode::Parameter => address(self.immediate(param)?),
+                    InstructionMode::Immediate => Err(VmErrorKind::ImmediateWrite(param)),
+                    InstructionMode::Relative => address(
+                        &self
+                            .immediate(param)?
+                            .checked_add(&self.program_state.relative_base)
+                            .ok_or(VmErrorKind::Overflow)?,
+                    ),
+                }
             }
 
-            fn param(&mut self, param: usize) -> &mut Val {
-                let loc = (*self.immediate(param)) as usize;
-                self.lookup(loc)
+            fn mode(&self, param: usize) -> InstructionMode {
+                self.opcode.mode(param)
             }
 
-            fn relative(&mut self, param: usize) -> &mut Val {
-                let loc = *self.immediate(param)
-                    + i128::try_from(self.program_state.relative_base).unwrap();
-                self.lookup(loc.try_into().unwrap())
+            /// Evaluate parameter <offset> for the current opcode
+            pub fn eval_param(&mut self, param: usize) -> Result<W, VmErrorKind<W>> {
+                let val = match self.mode(param) {
+                    InstructionMode::Immediate => self.immediate(param)?.clone(),
+                    _ => self.program.memory[self.address(param)?].clone(),
+                };
+                if self.trace {
+                    self.event.operands.push(val.clone());
+                }
+                Ok(val)
             }
 
-            /// Evaluate parameter <offset> for the current opcode
-            pub fn eval_param(&mut self, param: usize) -> &mut Val {
-                let mode = self.instrs.get(param).copied().unwrap_or_default();
-                match mode {
-                    InstructionMode::Parameter => self.param(param),
-                    InstructionMode::Immediate => self.immediate(param),
-                    InstructionMode::Relative => self.relative(param),
+            /// Write `val` to the location parameter <offset> refers to
+            pub fn write_param(&mut self, param: usize, val: W) -> Result<(), VmErrorKind<W>> {
+                let loc = self.address(param)?;
+                if self.trace {
+                    self.event.write = Some((loc, val.clone()));
                 }
+                self.program.memory[loc] = val;
+                Ok(())
+            }
+
+            fn jump(&mut self, param: usize) -> Result<StepOutcome, VmErrorKind<W>> {
+                self.program_state.offset = address(&self.eval_param(param)?)?;
+                Ok(StepOutcome::Continue)
+            }
+
+            fn exec(
+                &mut self,
+                io: &mut (impl TryReadVal<W> + TryWriteVal<W>),
+            ) -> Result<StepOutcome, VmErrorKind<W>> {
+                match self.opcode.variant {
+                    OpcodeVariant::Add => {
+                        let res = self
+                            .eval_param(0)?
+                            .checked_add(&self.eval_param(1)?)
+                            .ok_or(VmErrorKind::Overflow)?;
+                        self.write_param(2, res)?;
+                    }
+                    OpcodeVariant::Mult => {
+                        let res = self
+                            .eval_param(0)?
+                            .checked_mul(&self.eval_param(1)?)
+                            .ok_or(VmErrorKind::Overflow)?;
+                        self.write_param(2, res)?;
+                    }
+                    OpcodeVariant::Halt => {
+                        return Ok(StepOutcome::Halted);
+                    }
+                    OpcodeVariant::Input => {
+                        let Some(entered) = io.try_read_val() else {
+                            return Ok(StepOutcome::NeedsInput);
+                        };
+                        self.write_param(0, entered)?;
+                    }
+                    OpcodeVariant::Output => {
+                        let to_output = self.eval_param(0)?;
+                        if io.try_write_val(to_output).is_none() {
+                            return Ok(StepOutcome::OutputBlocked);
+                        }
+                    }
+                    OpcodeVariant::JumpIfTrue => {
+                        if !self.eval_param(0)?.is_zero() {
+                            return self.jump(1);
+                        }
+                    }
+                    OpcodeVariant::JumpIfFalse => {
+                        if self.eval_param(0)?.is_zero() {
+                            return self.jump(1);
+                        }
+                    }
+                    OpcodeVariant::LessThan => {
+                        let res = if self.eval_param(0)? < self.eval_param(1)? {
+                            W::one()
+                        } else {
+                            W::zero()
+                        };
+                        self.write_param(2, res)?;
+                    }
+                    OpcodeVariant::Equals => {
+                        let res = if self.eval_param(0)? == self.eval_param(1)? {
+                            W::one()
+                        } else {
+                            W::zero()
+                        };
+                        self.write_param(2, res)?;
+                    }
+                    OpcodeVariant::RelativeBaseOffset => {
+                        let offset = self.eval_param(0)?;
+                        self.program_state.relative_base = self
+                            .program_state
+                            .relative_base
+                            .checked_add(&offset)
+                            .ok_or(VmErrorKind::Overflow)?;
+                    }
+                }
+                self.program_state.offset = offset_address(
+                    self.program_state.offset,
+                    self.opcode.variant.instruction_count() + 1,
+                )?;
+                Ok(StepOutcome::Continue)
             }
         }
 
+        let event = if T::ENABLED {
+            TraceEvent {
+                ip: program_state.offset,
+                instruction: program.memory[program_state.offset].clone(),
+                ..Default::default()
+            }
+        } else {
+            TraceEvent::default()
+        };
         let mut ctx = EvalCtx {
             program_state,
-            instrs: &self.instrs,
+            opcode: self,
             program,
+            event,
+            trace: T::ENABLED,
         };
-
-        match self.variant {
-            OpcodeVariant::Add => {
-                let res = *ctx.eval_param(0) + *ctx.eval_param(1);
-                *ctx.eval_param(2) = res;
-            }
-            OpcodeVariant::Mult => {
-                let res = *ctx.eval_param(0) * *ctx.eval_param(1);
-                *ctx.eval_param(2) = res;
-            }
-            OpcodeVariant::Halt => {
-                return None;
-            }
-            OpcodeVariant::Input => {
-                let entered = io.try_read_val();
-                *ctx.eval_param(0) = entered?;
-            }
-            OpcodeVariant::Output => {
-                let to_output = ctx.eval_param(0);
-                io.try_write_val(*to_output)?;
-            }
-            OpcodeVariant::JumpIfTrue => {
-                if *ctx.eval_param(0) != 0 {
-                    let program_state = ProgramState {
-                        offset: (*ctx.eval_param(1)).try_into().unwrap(),
-                        ..ctx.program_state
-                    };
-                    return Some(program_state);
-                }
-            }
-            OpcodeVariant::JumpIfFalse => {
-                if *ctx.eval_param(0) == 0 {
-                    let program_state = ProgramState {
-                        offset: (*ctx.eval_param(1)).try_into().unwrap(),
-                        ..ctx.program_state
-                    };
-                    return Some(program_state);
-                }
-            }
-            OpcodeVariant::LessThan => {
-                let res = if *ctx.eval_param(0) < *ctx.eval_param(1) {
-                    1
-                } else {
-                    0
-                };
-                *ctx.eval_param(2) = res;
-            }
-            OpcodeVariant::Equals => {
-                let res = if *ctx.eval_param(0) == *ctx.eval_param(1) {
-                    1
-                } else {
-                    0
-                };
-                *ctx.eval_param(2) = res;
-            }
-            OpcodeVariant::RelativeBaseOffset => {
-                let new_relative_base =
-                    i128::try_from(ctx.program_state.relative_base).unwrap() + *ctx.eval_param(0);
-                ctx.program_state.relative_base = new_relative_base.try_into().unwrap();
-            }
+        let outcome = ctx.exec(io)?;
+        if T::ENABLED && matches!(outcome, StepOutcome::Continue | StepOutcome::Halted) {
+            tracer.trace(&ctx.event);
         }
-        let program_state = ProgramState {
-            offset: ctx.program_state.offset + self.variant.instruction_count() + 1,
-            ..ctx.program_state
-        };
-        Some(program_state)
+        Ok(outcome)
+    }
+
+    pub fn mode(&self, param: usize) -> InstructionMode {
+        self.modes[param]
     }
 
-    pub fn new(instr_raw: Val) -> Self {
-        let opcode_raw = instr_raw % 100;
-        let instrs = {
-            let code = (instr_raw - opcode_raw) / 100;
-            code.to_string()
-                .chars()
-                .map(|num| InstructionMode::try_from((num as u8) - b'0').unwrap())
-                .rev()
-                .collect()
+    /// Decode an instruction; anything that doesn't fit an `i64` can't be one
+    pub fn new<W: Word>(instr_raw: &W) -> Result<Self, VmErrorKind<W>> {
+        let Some(instr_raw) = instr_raw.to_i64().filter(|&raw| raw >= 0) else {
+            return Err(VmErrorKind::UnknownOpcode);
         };
+        let mut modes = [InstructionMode::default(); MAX_PARAMS];
+        let mut digits = instr_raw / 100;
+        let mut param = 0;
+        while digits > 0 {
+            let digit = (digits % 10) as u8;
+            let mode = InstructionMode::try_from(digit).map_err(|_| VmErrorKind::BadMode(digit))?;
+            // leading digits past the last parameter must still be valid modes
+            if let Some(slot) = modes.get_mut(param) {
+                *slot = mode;
+            }
+            digits /= 10;
+            param += 1;
+        }
 
-        let variant = OpcodeVariant::try_from((opcode_raw % 100) as i32).unwrap();
-        Self { variant, instrs }
+        let variant = OpcodeVariant::try_from((instr_raw % 100) as i32)
+            .map_err(|_| VmErrorKind::UnknownOpcode)?;
+        Ok(Self { variant, modes })
     }
 }
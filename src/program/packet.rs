@@ -0,0 +1,223 @@
+use anyhow::Context;
+
+use super::{
+    Program, Val,
+    machine::{Event, Machine},
+    word::Word,
+};
+
+/// An `(x, y)` message for the computer at `dest`, sent as three consecutive outputs
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packet<W = Val> {
+    pub dest: W,
+    pub x: W,
+    pub y: W,
+}
+
+#[derive(Clone)]
+struct Computer<W> {
+    machine: Machine<W>,
+    /// Outputs of a packet that's still being sent
+    partial: Vec<W>,
+}
+
+/// Computers that talk by sending each other packets.
+///
+/// Reading input never blocks: a computer with nothing queued reads `-1`, so
+/// every computer gets to run in each round, whether or not it has mail.
+#[derive(Clone)]
+pub struct PacketNetwork<W = Val> {
+    computers: Vec<Computer<W>>,
+    /// Whether the last round ended with nothing sent and nothing queued
+    idle: bool,
+}
+
+impl<W: Word> PacketNetwork<W> {
+    /// Boot `size` copies of `program`, giving each its address as its first input
+    pub fn new(program: &Program<W>, size: usize) -> anyhow::Result<Self> {
+        let computers = (0..size)
+            .map(|addr| {
+                let mut machine = Machine::new(program.clone());
+                machine.provide_input(W::from_usize(addr).context("address does not fit a word")?);
+                Ok(Computer {
+                    machine,
+                    partial: vec![],
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            computers,
+            idle: false,
+        })
+    }
+
+    /// Queue `packet` for its destination, returning it back if no computer has that address
+    pub fn send(&mut self, packet: Packet<W>) -> Option<Packet<W>> {
+        let computer = packet
+            .dest
+            .to_usize()
+            .and_then(|addr| self.computers.get_mut(addr));
+        let Some(computer) = computer else {
+            return Some(packet);
+        };
+        computer.machine.provide_input(packet.x);
+        computer.machine.provide_input(packet.y);
+        None
+    }
+
+    /// Run each computer until it has read `-1` and waits for input again, delivering packets as
+    /// they're sent.
+    ///
+    /// Returns the packets addressed outside the network, in the order they were sent.
+    pub fn run_round(&mut self) -> anyhow::Result<Vec<Packet<W>>> {
+        let mut sent = false;
+        let mut outside = vec![];
+        for addr in 0..self.computers.len() {
+            let mut polled = false;
+            loop {
+                let computer = &mut self.computers[addr];
+                let val = match computer.machine.run() {
+                    Ok(Event::Output(val)) => val,
+                    Ok(Event::NeedsInput) if !polled => {
+                        computer.machine.provide_input(-W::one());
+                        polled = true;
+                        continue;
+                    }
+                    Ok(Event::NeedsInput) => break,
+                    Ok(Event::Halted) => anyhow::bail!("computer {} halted", addr),
+                    Err(err) => return Err(err).with_context(|| format!("computer {}", addr)),
+                };
+                computer.partial.push(val);
+                if let [dest, x, y] = &computer.partial[..] {
+                    let packet = Packet {
+                        dest: dest.clone(),
+                        x: x.clone(),
+                        y: y.clone(),
+                    };
+                    computer.partial.clear();
+                    sent = true;
+                    outside.extend(self.send(packet));
+                }
+            }
+        }
+        self.idle = !sent
+            && self
+                .computers
+                .iter()
+                .all(|c| c.machine.pending_input() == 0);
+        Ok(outside)
+    }
+
+    /// Whether the last round sent nothing and left nothing waiting to be read
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+}
+
+/// Watches a network's traffic to `address`, and restarts the network when it falls idle
+/// by resending the last packet it saw to computer 0
+pub struct Nat<W = Val> {
+    pub address: W,
+    last: Option<Packet<W>>,
+}
+
+impl<W: Word> Nat<W> {
+    pub fn new(address: W) -> Self {
+        Self {
+            address,
+            last: None,
+        }
+    }
+
+    /// Remember the latest of `packets` sent to the NAT, returning any others
+    pub fn receive(&mut self, packets: Vec<Packet<W>>) -> Vec<Packet<W>> {
+        let (mine, others) = packets
+            .into_iter()
+            .partition::<Vec<_>, _>(|packet| packet.dest == self.address);
+        if let Some(last) = mine.into_iter().last() {
+            self.last = Some(last);
+        }
+        others
+    }
+
+    /// If `network` is idle, send it the last packet received, returning what was sent
+    pub fn wake(&mut self, network: &mut PacketNetwork<W>) -> Option<Packet<W>> {
+        if !network.is_idle() {
+            return None;
+        }
+        let packet = Packet {
+            dest: W::zero(),
+            ..self.last.clone()?
+        };
+        network.send(packet.clone());
+        Some(packet)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::asm::assemble;
+
+    #[test]
+    fn test_packets() {
+        // computer 0 greets computer 1, and everyone forwards what they get to 255, doubling y
+        let program = assemble(
+            "
+                    in [addr]
+                    jt [addr], listen
+                    out 1
+                    out 7
+                    out 8
+            listen: in [x]
+                    eq [x], -1, [idle]
+                    jt [idle], listen
+                    in [y]
+                    mul [y], 2, [y]
+                    out 255
+                    out [x]
+                    out [y]
+                    jt 1, listen
+            addr:   data 0
+            x:      data 0
+            y:      data 0
+            idle:   data 0
+            ",
+        )
+        .unwrap();
+        let mut network = PacketNetwork::new(&program, 2).unwrap();
+        let mut nat = Nat::new(255);
+
+        let packets = network.run_round().unwrap();
+        assert_eq!(
+            packets,
+            vec![Packet {
+                dest: 255,
+                x: 7,
+                y: 16
+            }]
+        );
+        assert!(nat.receive(packets).is_empty());
+        assert!(!network.is_idle());
+        assert_eq!(nat.wake(&mut network), None);
+
+        assert!(network.run_round().unwrap().is_empty());
+        assert!(network.is_idle());
+        assert_eq!(
+            nat.wake(&mut network),
+            Some(Packet {
+                dest: 0,
+                x: 7,
+                y: 16
+            })
+        );
+        assert_eq!(
+            network.run_round().unwrap(),
+            vec![Packet {
+                dest: 255,
+                x: 7,
+                y: 32
+            }]
+        );
+    }
+}
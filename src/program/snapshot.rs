@@ -0,0 +1,134 @@
+use std::{collections::HashMap, str::FromStr};
+
+use anyhow::Context;
+use itertools::Itertools;
+
+use super::{Program, ProgramState, Val, memory::Memory, word::Word};
+use crate::utls::parse_lines;
+
+/// A paused machine's complete state: memory, registers and queued input.
+///
+/// Serializes to a line-based text format:
+///
+/// ```text
+/// ip: 12
+/// rb: 0
+/// input: 1,2
+/// memory: 3,9,8,9,10,9,4,9,99,-1,8
+/// sparse: 5000000=7
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot<W = Val> {
+    pub(super) memory: Memory<W>,
+    pub(super) state: ProgramState<W>,
+    pub(super) input: Vec<W>,
+}
+
+impl<W: Word> std::fmt::Display for Snapshot<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "ip: {}", self.state.offset)?;
+        writeln!(f, "rb: {}", self.state.relative_base)?;
+        writeln!(f, "input: {}", self.input.iter().join(","))?;
+        writeln!(f, "memory: {}", self.memory.dense().iter().join(","))?;
+        write!(
+            f,
+            "sparse: {}",
+            self.memory
+                .sparse()
+                .map(|(addr, val)| format!("{}={}", addr, val))
+                .join(",")
+        )
+    }
+}
+
+fn parse_vals<W: Word>(s: &str) -> anyhow::Result<Vec<W>> {
+    if s.is_empty() {
+        Ok(vec![])
+    } else {
+        Program::parse_code(s)
+    }
+}
+
+impl<W: Word> FromStr for Snapshot<W> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: HashMap<String, String> = parse_lines(s, |line| {
+            line.split_once(':')
+                .map(|(key, val)| (key.trim().to_owned(), val.trim().to_owned()))
+                .context("expected `<field>: <value>`")
+        })?
+        .into_iter()
+        .collect();
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .map(String::as_str)
+                .with_context(|| format!("snapshot is missing `{}`", key))
+        };
+
+        let sparse = field("sparse")?
+            .split(',')
+            .filter(|cell| !cell.trim().is_empty())
+            .map(|cell| -> anyhow::Result<_> {
+                let (addr, val) = cell
+                    .split_once('=')
+                    .with_context(|| format!("expected `<addr>=<value>`, found `{}`", cell))?;
+                Ok((addr.trim().parse()?, val.trim().parse()?))
+            })
+            .try_collect()?;
+        Ok(Self {
+            memory: Memory::from_parts(parse_vals(field("memory")?)?, sparse),
+            state: ProgramState {
+                offset: field("ip")?.parse().context("bad `ip`")?,
+                relative_base: field("rb")?.parse().context("bad `rb`")?,
+            },
+            input: parse_vals(field("input")?)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        asm::assemble,
+        machine::{Event, Machine},
+    };
+
+    #[test]
+    fn test_restore() {
+        let program = assemble(
+            "
+            loop:   in [x]
+                    add [x], [total], [total]
+                    out [total]
+                    jt 1, loop
+            x:      data 0
+            total:  data 0
+            ",
+        )
+        .unwrap();
+        let mut machine = Machine::new(program);
+        machine.provide_input(5);
+        assert_eq!(machine.run(), Ok(Event::Output(5)));
+        machine.memory_mut()[1_000_000] = 9;
+        machine.provide_input(7);
+
+        let snapshot = machine.snapshot();
+        let text = snapshot.to_string();
+        assert!(text.contains("input: 7\n"));
+        assert!(text.ends_with("sparse: 1000000=9"));
+        let loaded: Snapshot = text.parse().unwrap();
+        assert_eq!(loaded, snapshot);
+
+        assert_eq!(machine.run(), Ok(Event::Output(12)));
+        machine.restore(&loaded);
+        assert_eq!(machine.run(), Ok(Event::Output(12)));
+        machine.restore(&snapshot);
+        machine.provide_input(1);
+        assert_eq!(machine.run(), Ok(Event::Output(12)));
+        assert_eq!(machine.run(), Ok(Event::Output(13)));
+        assert_eq!(machine.memory()[1_000_000], 9);
+    }
+}
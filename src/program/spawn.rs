@@ -1,45 +1,228 @@
 use std::{
-    sync::mpsc::{self},
+    any::Any,
+    sync::{
+        Arc,
+        atomic::{AtomicU8, AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError, TryRecvError},
+    },
     thread::JoinHandle,
+    time::Duration,
 };
 
+use num_enum::TryFromPrimitive;
+
 use super::{
     Program, Val,
+    error::VmError,
     io::{TryReadVal, TryWriteVal},
+    limits::Limits,
+    word::Word,
 };
 
-pub struct ProgramHandle {
-    program_handle: JoinHandle<mpsc::Receiver<Val>>,
-    reader: mpsc::Receiver<Val>,
-    writer: mpsc::Sender<Val>,
+/// What a spawned program is doing
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive, derive_more::Display)]
+#[repr(u8)]
+pub enum Status {
+    #[display("running")]
+    Running,
+    #[display("waiting for input")]
+    WaitingForInput,
+    #[display("halted")]
+    Halted,
+    /// Stopped on a VM error or a panic; `join` says which
+    #[display("faulted")]
+    Faulted,
+}
+
+/// Why a spawned program didn't finish
+#[derive(Debug, derive_more::Display)]
+pub enum SpawnError<W = Val> {
+    #[display("{_0}")]
+    Vm(VmError<W>),
+    #[display("program thread panicked: {_0}")]
+    Panicked(String),
 }
 
-impl ProgramHandle {
-    pub fn join(self) -> impl TryReadVal {
-        self.program_handle.join().unwrap()
+impl<W: std::fmt::Debug + std::fmt::Display> std::error::Error for SpawnError<W> {}
+
+pub struct ProgramHandle<W = Val> {
+    program_handle: JoinHandle<Result<Program<W>, VmError<W>>>,
+    reader: mpsc::Receiver<W>,
+    writer: mpsc::Sender<W>,
+    status: Arc<AtomicU8>,
+    /// Inputs sent that the program hasn't read yet
+    pending: Arc<AtomicUsize>,
+}
+
+impl<W> ProgramHandle<W> {
+    /// Stop sending input and wait for the program to finish, returning it with its final
+    /// memory and any outputs that weren't received yet.
+    ///
+    /// A program still waiting for input then fails with an I/O error.
+    pub fn join(self) -> Result<(Program<W>, Vec<W>), SpawnError<W>> {
+        drop(self.writer);
+        let program = match self.program_handle.join() {
+            Ok(result) => result.map_err(SpawnError::Vm)?,
+            Err(payload) => return Err(SpawnError::Panicked(panic_message(payload))),
+        };
+        Ok((program, self.reader.try_iter().collect()))
+    }
+
+    /// The next output, if the program has produced one
+    pub fn try_recv(&self) -> Result<W, TryRecvError> {
+        self.reader.try_recv()
     }
 
-    pub fn try_read_val(&mut self) -> Option<Val> {
-        self.reader.recv().ok()
+    /// Wait up to `timeout` for the next output
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<W, RecvTimeoutError> {
+        self.reader.recv_timeout(timeout)
+    }
+
+    /// Only `WaitingForInput` once the program has read everything sent to it, so a program
+    /// in that state with no output waiting can't make progress until it's sent more
+    pub fn status(&self) -> Status {
+        // read first: the program marks itself running before it counts an input as read
+        let pending = self.pending.load(Ordering::Acquire);
+        let status = Status::try_from(self.status.load(Ordering::Acquire))
+            .expect("only statuses are stored");
+        if status == Status::WaitingForInput && pending > 0 {
+            return Status::Running;
+        }
+        // a thread that ended without saying how must have panicked
+        if self.program_handle.is_finished()
+            && matches!(status, Status::Running | Status::WaitingForInput)
+        {
+            Status::Faulted
+        } else {
+            status
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "unknown panic".to_owned(), |message| message.to_string()),
     }
 }
 
-impl TryWriteVal for ProgramHandle {
-    fn try_write_val(&mut self, val: Val) -> Option<()> {
-        self.writer.send(val).ok()
+/// Doesn't block; returns `None` if no output is ready yet
+impl<W> TryReadVal<W> for ProgramHandle<W> {
+    fn try_read_val(&mut self) -> Option<W> {
+        self.try_recv().ok()
     }
 }
 
-pub fn spawn(mut program: Program) -> ProgramHandle {
-    let (write_to_program, mut input) = mpsc::channel();
+impl<W> TryWriteVal<W> for ProgramHandle<W> {
+    fn try_write_val(&mut self, val: W) -> Option<()> {
+        self.pending.fetch_add(1, Ordering::AcqRel);
+        let sent = self.writer.send(val).ok();
+        if sent.is_none() {
+            self.pending.fetch_sub(1, Ordering::AcqRel);
+        }
+        sent
+    }
+}
+
+/// The spawned program's input, which records when the program is waiting on it
+struct Inbox<W> {
+    input: mpsc::Receiver<W>,
+    status: Arc<AtomicU8>,
+    pending: Arc<AtomicUsize>,
+}
+
+impl<W> Inbox<W> {
+    fn received(&self, val: W) -> W {
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+        val
+    }
+}
+
+impl<W> TryReadVal<W> for Inbox<W> {
+    fn try_read_val(&mut self) -> Option<W> {
+        match self.input.try_recv() {
+            Ok(val) => return Some(self.received(val)),
+            Err(TryRecvError::Disconnected) => return None,
+            Err(TryRecvError::Empty) => {}
+        }
+        self.status
+            .store(Status::WaitingForInput as u8, Ordering::Release);
+        let val = self.input.recv().ok();
+        self.status.store(Status::Running as u8, Ordering::Release);
+        val.map(|val| self.received(val))
+    }
+}
+
+pub fn spawn<W: Word>(program: Program<W>) -> ProgramHandle<W> {
+    spawn_limited(program, Limits::default())
+}
+
+pub fn spawn_limited<W: Word>(mut program: Program<W>, limits: Limits) -> ProgramHandle<W> {
+    let (write_to_program, input) = mpsc::channel();
     let (mut output, read_from_program) = mpsc::channel();
+    let status = Arc::new(AtomicU8::new(Status::Running as u8));
+    let pending = Arc::new(AtomicUsize::new(0));
+    let mut inbox = Inbox {
+        input,
+        status: status.clone(),
+        pending: pending.clone(),
+    };
     let handle = std::thread::spawn(move || {
-        program.eval(&mut input, &mut output);
-        input
+        let result = program.eval_limited(&mut inbox, &mut output, limits);
+        let status = match result {
+            Ok(_) => Status::Halted,
+            Err(_) => Status::Faulted,
+        };
+        inbox.status.store(status as u8, Ordering::Release);
+        result.map(|_| program)
     });
     ProgramHandle {
         program_handle: handle,
         reader: read_from_program,
         writer: write_to_program,
+        status,
+        pending,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utls::MyParse;
+
+    #[test]
+    fn test_lifecycle() {
+        // echoes one value into [0], then halts
+        let program: Program = Program::my_parse("3,0,4,0,99").unwrap();
+        let mut handle = spawn(program);
+        assert_eq!(handle.try_recv(), Err(TryRecvError::Empty));
+        while handle.status() != Status::WaitingForInput {
+            std::thread::yield_now();
+        }
+
+        handle.try_write_val(5).unwrap();
+        assert_eq!(handle.recv_timeout(Duration::from_secs(10)), Ok(5));
+        assert_eq!(
+            handle.recv_timeout(Duration::from_secs(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(handle.status(), Status::Halted);
+        let (program, outputs) = handle.join().unwrap();
+        assert_eq!(program.memory[0], 5);
+        assert!(outputs.is_empty());
+
+        // outputs nobody received come back from `join`
+        let program: Program = Program::my_parse("104,1,104,2,99").unwrap();
+        let handle = spawn(program);
+        assert_eq!(handle.recv_timeout(Duration::from_secs(10)), Ok(1));
+        assert_eq!(handle.join().unwrap().1, [2]);
+
+        let program: Program = Program::my_parse("3,0,99").unwrap();
+        let Err(err) = spawn(program).join() else {
+            panic!("program should run out of input");
+        };
+        assert_eq!(err.to_string(), "ip 0 (opcode 3): no input is available");
     }
 }
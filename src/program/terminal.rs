@@ -0,0 +1,160 @@
+use std::{collections::VecDeque, io::BufRead};
+
+use super::{
+    Program, Val,
+    machine::{Event, Machine},
+};
+
+/// Something an ASCII program printed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// A line of text, without its newline
+    Line(String),
+    /// A value that isn't ASCII, usually the answer the program worked out
+    Large(Val),
+}
+
+impl std::fmt::Display for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Output::Line(line) => write!(f, "{}", line),
+            Output::Large(val) => write!(f, "{}", val),
+        }
+    }
+}
+
+/// Talks to a program that reads and prints ASCII text, a line at a time.
+///
+/// Input comes from lines queued with `send_line`, then from the live input if
+/// there is one.
+pub struct AsciiTerminal {
+    machine: Machine,
+    script: VecDeque<String>,
+    live: Option<Box<dyn BufRead>>,
+    /// Text printed since the last newline
+    line: String,
+}
+
+impl AsciiTerminal {
+    pub fn new(program: Program) -> Self {
+        Self {
+            machine: Machine::new(program),
+            script: VecDeque::new(),
+            live: None,
+            line: String::new(),
+        }
+    }
+
+    /// Read lines from `input` once the queued ones run out, instead of failing
+    pub fn live(self, input: impl BufRead + 'static) -> Self {
+        Self {
+            live: Some(Box::new(input)),
+            ..self
+        }
+    }
+
+    /// Queue `line` to be typed in, followed by a newline
+    pub fn send_line(&mut self, line: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            line.is_ascii() && !line.contains('\n'),
+            "`{}` is not a single line of ASCII",
+            line.escape_debug()
+        );
+        self.script.push_back(line.to_owned());
+        Ok(())
+    }
+
+    fn next_line(&mut self) -> anyhow::Result<String> {
+        if let Some(line) = self.script.pop_front() {
+            return Ok(line);
+        }
+        let Some(live) = &mut self.live else {
+            anyhow::bail!("program wants more input than was given");
+        };
+        let mut line = String::new();
+        anyhow::ensure!(
+            live.read_line(&mut line)? > 0,
+            "input ended while the program wanted more"
+        );
+        let line = line.trim_end_matches(['\r', '\n']);
+        anyhow::ensure!(line.is_ascii(), "`{}` is not ASCII", line);
+        Ok(line.to_owned())
+    }
+
+    /// The next thing the program prints, or `None` once it halts
+    pub fn next_output(&mut self) -> anyhow::Result<Option<Output>> {
+        loop {
+            match self.machine.run()? {
+                Event::Output(val) => match u8::try_from(val).ok().filter(u8::is_ascii) {
+                    Some(b'\n') => return Ok(Some(Output::Line(std::mem::take(&mut self.line)))),
+                    Some(c) => self.line.push(c.into()),
+                    None => return Ok(Some(Output::Large(val))),
+                },
+                Event::NeedsInput => {
+                    let line = self.next_line()?;
+                    for c in line.bytes().chain(*b"\n") {
+                        self.machine.provide_input(c.into());
+                    }
+                }
+                Event::Halted if self.line.is_empty() => return Ok(None),
+                Event::Halted => return Ok(Some(Output::Line(std::mem::take(&mut self.line)))),
+            }
+        }
+    }
+
+    /// Run until the program halts, returning everything it printed
+    pub fn run(&mut self) -> anyhow::Result<Vec<Output>> {
+        let mut outputs = vec![];
+        while let Some(output) = self.next_output()? {
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::asm::assemble;
+
+    #[test]
+    fn test_lines() {
+        // prints "ok?", echoes back the first character it reads, then prints 1000
+        let program = assemble(
+            "
+                    out 111
+                    out 107
+                    out 63
+                    out 10
+                    in [c]
+                    out [c]
+            skip:   in [rest]
+                    eq [rest], 10, [done]
+                    jf [done], skip
+                    out 10
+                    out 1000
+                    hlt
+            c:      data 0
+            rest:   data 0
+            done:   data 0
+            ",
+        )
+        .unwrap();
+        let mut terminal = AsciiTerminal::new(program.clone());
+        terminal.send_line("yes").unwrap();
+        assert_eq!(
+            terminal.run().unwrap(),
+            vec![
+                Output::Line("ok?".to_owned()),
+                Output::Line("y".to_owned()),
+                Output::Large(1000)
+            ]
+        );
+
+        let mut terminal = AsciiTerminal::new(program.clone()).live(&b"no\n"[..]);
+        assert_eq!(terminal.run().unwrap()[1], Output::Line("n".to_owned()));
+
+        let err = AsciiTerminal::new(program).run().unwrap_err();
+        assert_eq!(err.to_string(), "program wants more input than was given");
+    }
+}
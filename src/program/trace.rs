@@ -0,0 +1,193 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use itertools::Itertools;
+
+use super::{Val, opcode::Opcode, word::Word};
+
+/// One executed instruction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraceEvent<W = Val> {
+    pub ip: usize,
+    /// The raw instruction, modes included
+    pub instruction: W,
+    /// Values of the parameters the instruction read, in order
+    pub operands: Vec<W>,
+    /// Address and value the instruction wrote, if any
+    pub write: Option<(usize, W)>,
+}
+
+impl<W: Word> TraceEvent<W> {
+    pub fn mnemonic(&self) -> String {
+        Opcode::new(&self.instruction)
+            .map(|opcode| opcode.variant.to_string())
+            .unwrap_or_else(|_| "???".to_owned())
+    }
+}
+
+impl<W: Word> std::fmt::Display for TraceEvent<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}: {}", self.ip, self.mnemonic())?;
+        if !self.operands.is_empty() {
+            write!(f, " {}", self.operands.iter().join(", "))?;
+        }
+        if let Some((addr, val)) = &self.write {
+            write!(f, " => [{}] = {}", addr, val)?;
+        }
+        Ok(())
+    }
+}
+
+/// Observes every instruction the VM executes; blocked or faulting instructions aren't reported
+pub trait Tracer<W = Val> {
+    /// Whether events are wanted at all, so the VM can skip recording them
+    const ENABLED: bool = true;
+
+    fn trace(&mut self, event: &TraceEvent<W>);
+}
+
+/// The default tracer, which compiles away
+pub struct NoTrace;
+
+impl<W> Tracer<W> for NoTrace {
+    const ENABLED: bool = false;
+
+    fn trace(&mut self, _event: &TraceEvent<W>) {}
+}
+
+impl<W, A: Tracer<W>, B: Tracer<W>> Tracer<W> for (A, B) {
+    const ENABLED: bool = A::ENABLED || B::ENABLED;
+
+    fn trace(&mut self, event: &TraceEvent<W>) {
+        self.0.trace(event);
+        self.1.trace(event);
+    }
+}
+
+impl<W, T: Tracer<W>> Tracer<W> for Option<T> {
+    const ENABLED: bool = T::ENABLED;
+
+    fn trace(&mut self, event: &TraceEvent<W>) {
+        if let Some(tracer) = self {
+            tracer.trace(event);
+        }
+    }
+}
+
+/// Writes one line per executed instruction, keeping the first write error for `finish`
+pub struct WriteTracer<Out: Write> {
+    out: Out,
+    error: Option<io::Error>,
+}
+
+impl<Out: Write> WriteTracer<Out> {
+    pub fn new(out: Out) -> Self {
+        Self { out, error: None }
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error {
+            Some(err) => Err(err),
+            None => self.out.flush(),
+        }
+    }
+}
+
+impl<W: Word, Out: Write> Tracer<W> for WriteTracer<Out> {
+    fn trace(&mut self, event: &TraceEvent<W>) {
+        if self.error.is_none()
+            && let Err(err) = writeln!(self.out, "{}", event)
+        {
+            self.error = Some(err);
+        }
+    }
+}
+
+/// Execution counts per address and per opcode
+#[derive(Default)]
+pub struct Profile {
+    pub steps: u64,
+    pub addresses: HashMap<usize, u64>,
+    pub opcodes: HashMap<String, u64>,
+}
+
+impl Profile {
+    /// The `count` most executed addresses, hottest first
+    pub fn hottest(&self, count: usize) -> Vec<(usize, u64)> {
+        self.addresses
+            .iter()
+            .map(|(&addr, &hits)| (addr, hits))
+            .sorted_by_key(|&(addr, hits)| (std::cmp::Reverse(hits), addr))
+            .take(count)
+            .collect()
+    }
+}
+
+impl<W: Word> Tracer<W> for Profile {
+    fn trace(&mut self, event: &TraceEvent<W>) {
+        self.steps += 1;
+        *self.addresses.entry(event.ip).or_default() += 1;
+        *self.opcodes.entry(event.mnemonic()).or_default() += 1;
+    }
+}
+
+impl std::fmt::Display for Profile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} instructions executed", self.steps)?;
+        writeln!(f, "opcodes:")?;
+        for (mnemonic, hits) in self
+            .opcodes
+            .iter()
+            .sorted_by_key(|&(mnemonic, hits)| (std::cmp::Reverse(hits), mnemonic))
+        {
+            writeln!(f, "  {:<4} {}", mnemonic, hits)?;
+        }
+        write!(f, "hottest addresses:")?;
+        for (addr, hits) in self.hottest(10) {
+            write!(f, "\n  {:04} {}", addr, hits)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        asm::assemble,
+        machine::{Event, Machine},
+    };
+
+    #[test]
+    fn test_trace_and_profile() {
+        let program = assemble(
+            "
+            # counts [n] down to zero
+            loop:   add [n], -1, [n]
+                    jt [n], loop
+                    hlt
+            n:      data 3
+            ",
+        )
+        .unwrap();
+        let mut machine = Machine::new(program);
+        let mut tracer = (WriteTracer::new(vec![]), Profile::default());
+        while machine.step_traced(&mut tracer).unwrap() != Some(Event::Halted) {}
+
+        let (lines, profile) = tracer;
+        let lines = String::from_utf8(lines.out).unwrap();
+        assert_eq!(
+            lines.lines().take(3).collect_vec(),
+            vec![
+                "0000: add 3, -1 => [8] = 2",
+                "0004: jt 2, 0",
+                "0000: add 2, -1 => [8] = 1"
+            ]
+        );
+        assert_eq!(profile.steps, 7);
+        assert_eq!(profile.opcodes["add"], 3);
+        assert_eq!(profile.hottest(2), vec![(0, 3), (4, 3)]);
+    }
+}
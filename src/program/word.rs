@@ -0,0 +1,46 @@
+use std::{
+    fmt::{Debug, Display},
+    hash::Hash,
+    str::FromStr,
+};
+
+use num::{CheckedAdd, CheckedMul, FromPrimitive, Signed, ToPrimitive};
+
+/// A machine word the VM can compute with, e.g. `i64`, `i128` (`Val`) or `num::BigInt`
+pub trait Word:
+    Clone
+    + Debug
+    + Display
+    + Default
+    + Ord
+    + Hash
+    + FromStr<Err: std::error::Error + Send + Sync + 'static>
+    + Signed
+    + CheckedAdd
+    + CheckedMul
+    + ToPrimitive
+    + FromPrimitive
+    + Send
+    + Sync
+    + 'static
+{
+}
+
+impl<W> Word for W where
+    W: Clone
+        + Debug
+        + Display
+        + Default
+        + Ord
+        + Hash
+        + FromStr<Err: std::error::Error + Send + Sync + 'static>
+        + Signed
+        + CheckedAdd
+        + CheckedMul
+        + ToPrimitive
+        + FromPrimitive
+        + Send
+        + Sync
+        + 'static
+{
+}
@@ -0,0 +1,226 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::Context;
+
+use crate::utls::timing::timed;
+
+#[derive(
+    Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum, derive_more::Display,
+)]
+pub enum Part {
+    #[value(name = "1")]
+    #[display("1")]
+    One,
+    #[value(name = "2")]
+    #[display("2")]
+    Two,
+}
+
+impl Part {
+    /// The parts to run when `part` was (maybe) requested on the command line
+    pub fn selected(part: Option<Part>) -> Vec<Part> {
+        match part {
+            Some(part) => vec![part],
+            None => vec![Part::One, Part::Two],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DaySelection {
+    All,
+    Day(u32),
+}
+
+impl FromStr for DaySelection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if s.eq_ignore_ascii_case("all") {
+            return Ok(Self::All);
+        }
+        match s.parse() {
+            Ok(day @ 1..=25) => Ok(Self::Day(day)),
+            _ => anyhow::bail!("expected a day between 1 and 25 or `all`, got `{}`", s),
+        }
+    }
+}
+
+/// Where puzzle input is read from
+#[derive(Clone, Debug)]
+pub enum InputSource {
+    /// `<dir>/<year>-<day>.txt`, so it can serve every day
+    Dir(PathBuf),
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputSource {
+    pub fn read(&self, year: u32, day: u32) -> anyhow::Result<String> {
+        match self {
+            InputSource::Dir(dir) => read_file(&dir.join(format!("{}-{:02}.txt", year, day))),
+            InputSource::File(path) => read_file(path),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .context("could not read puzzle input from stdin")?;
+                Ok(input)
+            }
+        }
+    }
+}
+
+fn read_file(path: &Path) -> anyhow::Result<String> {
+    std::fs::read_to_string(path)
+        .with_context(|| format!("could not read puzzle input from {}", path.display()))
+}
+
+/// A puzzle answer; most are numbers, but some (rendered images, digit strings) are text
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum Answer {
+    #[display("{_0}")]
+    Num(i128),
+    #[display("{_0}")]
+    Text(String),
+}
+
+macro_rules! answer_from_num {
+    ($($num:ty),*) => {
+        $(
+            impl From<$num> for Answer {
+                fn from(value: $num) -> Self {
+                    Self::Num(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_num!(i32, i64, i128, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+pub trait Solution {
+    type Input;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input>;
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer>;
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer>;
+
+    fn part(input: &Self::Input, part: Part) -> anyhow::Result<Answer> {
+        match part {
+            Part::One => Self::part_1(input),
+            Part::Two => Self::part_2(input),
+        }
+    }
+}
+
+pub struct DayReport {
+    pub parse: Duration,
+    pub parts: Vec<PartReport>,
+}
+
+pub struct PartReport {
+    pub part: Part,
+    pub answer: Answer,
+    pub elapsed: Duration,
+}
+
+/// Type-erased entry point, so years can dispatch on the day number
+pub type Solver = fn(&str, &[Part]) -> anyhow::Result<DayReport>;
+
+pub fn solve<S: Solution>(input: &str, parts: &[Part]) -> anyhow::Result<DayReport> {
+    solve_with::<S>(input, parts, |input| input)
+}
+
+/// Like `solve`, passing the parsed input through `prepare` before solving
+pub fn solve_with<S: Solution>(
+    input: &str,
+    parts: &[Part],
+    prepare: impl FnOnce(S::Input) -> S::Input,
+) -> anyhow::Result<DayReport> {
+    let (input, parse) = timed(|| S::parse(input));
+    let input = prepare(input.context("could not parse puzzle input")?);
+    let parts = parts
+        .iter()
+        .map(|&part| {
+            let (answer, elapsed) = timed(|| S::part(&input, part));
+            Ok(PartReport {
+                part,
+                answer: answer?,
+                elapsed,
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    Ok(DayReport { parse, parts })
+}
+
+/// Every implemented day of one year
+pub struct Year {
+    pub year: u32,
+    pub days: &'static [u32],
+    pub solver: fn(u32) -> Option<Solver>,
+}
+
+impl Year {
+    pub fn selected_days(&self, day: DaySelection) -> Vec<u32> {
+        match day {
+            DaySelection::All => self.days.to_vec(),
+            DaySelection::Day(day) => vec![day],
+        }
+    }
+
+    pub fn solver(&self, day: u32) -> anyhow::Result<Solver> {
+        (self.solver)(day)
+            .with_context(|| format!("day {} of {} is not implemented", day, self.year))
+    }
+
+    pub fn run_day(
+        &self,
+        day: u32,
+        parts: &[Part],
+        input: &InputSource,
+    ) -> anyhow::Result<DayReport> {
+        let solver = self.solver(day)?;
+        let input = input.read(self.year, day)?;
+        solver(&input, parts).with_context(|| format!("{} day {} failed", self.year, day))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::year_2019;
+
+    #[test]
+    fn test_day_selection() {
+        assert_eq!("all".parse::<DaySelection>().unwrap(), DaySelection::All);
+        assert_eq!("ALL".parse::<DaySelection>().unwrap(), DaySelection::All);
+        assert_eq!("7".parse::<DaySelection>().unwrap(), DaySelection::Day(7));
+        for bad in ["0", "26", "seven", ""] {
+            assert_eq!(
+                bad.parse::<DaySelection>().unwrap_err().to_string(),
+                format!("expected a day between 1 and 25 or `all`, got `{}`", bad)
+            );
+        }
+
+        let year = &year_2019::YEAR;
+        assert!(year.solver(23).is_ok());
+        assert_eq!(
+            year.solver(24).err().unwrap().to_string(),
+            "day 24 of 2019 is not implemented"
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, IsTerminal, Read, Write},
+    path::Path,
+};
+
+use anyhow::Context;
+use itertools::Itertools;
+use num::BigInt;
+
+use crate::{
+    answers::{AnswerBook, Verdict},
+    cli::{
+        AsciiArgs, AsmArgs, BenchArgs, DebugArgs, DisasmArgs, IntcodeBenchArgs, ProgramArgs,
+        RecordArgs, ReplayArgs, RunArgs, TraceArgs, TranscriptArgs, VerifyArgs, WordSize,
+    },
+    program::{
+        Program, Val, asm,
+        debugger::Debugger,
+        disasm,
+        io::{
+            Joint, TryReadVal, View,
+            text::{TextInput, TextOutput},
+            transcript::{Recording, Replay},
+        },
+        limits::Limits,
+        machine::{Event, Machine},
+        memory::Memory,
+        terminal::AsciiTerminal,
+        trace::{Profile, WriteTracer},
+        word::Word,
+    },
+    puzzle::{Answer, DaySelection, InputSource, Part, Year},
+    utls::{MyParse, timing::Summary},
+    year_2019,
+};
+
+fn find_year(year: u32) -> anyhow::Result<&'static Year> {
+    match year {
+        2019 => Ok(&year_2019::YEAR),
+        year => anyhow::bail!("year {} is not implemented", year),
+    }
+}
+
+/// Days to run; a single input file or stdin can only serve a single day
+fn selected_days(year: &Year, args: &RunArgs) -> anyhow::Result<Vec<u32>> {
+    let input = args.input_source();
+    anyhow::ensure!(
+        args.day != DaySelection::All || matches!(input, InputSource::Dir(_)),
+        "--input can only be used with a single day"
+    );
+    Ok(year.selected_days(args.day))
+}
+
+pub fn run(args: &RunArgs) -> anyhow::Result<()> {
+    let year = find_year(args.year)?;
+    let input = args.input_source();
+    for day in selected_days(year, args)? {
+        let report = year.run_day(day, &Part::selected(args.part), &input)?;
+        println!("{} day {} parse [{:.2?}]", year.year, day, report.parse);
+        for part in report.parts {
+            let answer = part.answer.to_string();
+            // rendered images start on their own line
+            let sep = if answer.contains('\n') { "\n" } else { " " };
+            println!(
+                "{} day {} part {} [{:.2?}]:{}{}",
+                year.year, day, part.part, part.elapsed, sep, answer
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn verify(args: &VerifyArgs) -> anyhow::Result<()> {
+    let year = find_year(args.run.year)?;
+    let input = args.run.input_source();
+    let book = AnswerBook::load(&args.answers_dir, year.year)?;
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+    for day in selected_days(year, &args.run)? {
+        let report = match year.run_day(day, &Part::selected(args.run.part), &input) {
+            Ok(report) => report,
+            Err(err) => {
+                failed += 1;
+                println!("{} day {}: ERROR {:#}", year.year, day, err);
+                continue;
+            }
+        };
+        for part in report.parts {
+            let answer = part.answer;
+            let part = part.part;
+            let verdict = match book.check(day, part, &answer) {
+                Verdict::Pass => {
+                    passed += 1;
+                    "PASS".to_owned()
+                }
+                Verdict::Fail { expected } => {
+                    failed += 1;
+                    format!("FAIL (expected {}, got {})", expected, answer)
+                }
+                Verdict::Missing => {
+                    missing += 1;
+                    format!("MISSING (got {})", answer)
+                }
+            };
+            println!("{} day {} part {}: {}", year.year, day, part, verdict);
+        }
+    }
+    println!("{} passed, {} failed, {} missing", passed, failed, missing);
+    anyhow::ensure!(failed == 0, "{} answers did not verify", failed);
+    Ok(())
+}
+
+pub fn record(args: &RecordArgs) -> anyhow::Result<()> {
+    let year = find_year(args.run.year)?;
+    let mut book = AnswerBook::load(&args.answers_dir, year.year)?;
+    let recorded = match &args.answer {
+        Some(answer) => {
+            let (DaySelection::Day(day), Some(part)) = (args.run.day, args.run.part) else {
+                anyhow::bail!("--answer needs a single --day and --part");
+            };
+            vec![(day, part, Answer::Text(answer.clone()))]
+        }
+        None => {
+            let input = args.run.input_source();
+            let mut recorded = vec![];
+            for day in selected_days(year, &args.run)? {
+                let report = year.run_day(day, &Part::selected(args.run.part), &input)?;
+                for part in report.parts {
+                    recorded.push((day, part.part, part.answer));
+                }
+            }
+            recorded
+        }
+    };
+    for (day, part, answer) in recorded {
+        book.record(day, part, &answer)?;
+        println!(
+            "recorded {} day {} part {}: {}",
+            year.year, day, part, answer
+        );
+    }
+    book.save()
+}
+
+pub fn bench(args: &BenchArgs) -> anyhow::Result<()> {
+    anyhow::ensure!(args.runs > 0, "--runs must be positive");
+    bench_days(args)
+}
+
+/// Time each day's solution once per Intcode dispatch mode
+pub fn intcode_bench(args: &IntcodeBenchArgs) -> anyhow::Result<()> {
+    anyhow::ensure!(args.runs > 0, "--runs must be positive");
+    let year = &year_2019::YEAR;
+    let input_source = InputSource::Dir(args.inputs_dir.clone());
+    for &day in &args.days {
+        let solver = |cached| {
+            year_2019::intcode_solver(day, cached)
+                .with_context(|| format!("no Intcode benchmark for day {}", day))
+        };
+        let modes = [
+            (solver(false)?, "decoding every step"),
+            (solver(true)?, "cached decoding"),
+        ];
+        let input = input_source.read(year.year, day)?;
+        let label = format!("{} day {}", year.year, day);
+        let mut expected = None;
+        for (solver, mode) in modes {
+            let mut parts: BTreeMap<Part, Vec<_>> = BTreeMap::new();
+            for _ in 0..args.runs {
+                let report = solver(&input, &Part::selected(None))
+                    .with_context(|| format!("{} failed", label))?;
+                let answers = report.parts.iter().map(|part| &part.answer).join(",");
+                anyhow::ensure!(
+                    expected.get_or_insert_with(|| answers.clone()) == &answers,
+                    "{} changed the answers for {}",
+                    mode,
+                    label
+                );
+                for part in report.parts {
+                    parts.entry(part.part).or_default().push(part.elapsed);
+                }
+            }
+            for (part, samples) in parts {
+                println!(
+                    "{:<14} part {}  {:<20} {}",
+                    label,
+                    part,
+                    mode,
+                    Summary::of(&samples).unwrap()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn bench_days(args: &BenchArgs) -> anyhow::Result<()> {
+    let year = find_year(args.run.year)?;
+    let input_source = args.run.input_source();
+    for day in selected_days(year, &args.run)? {
+        let solver = year.solver(day)?;
+        let input = input_source.read(year.year, day)?;
+        let mut parse = vec![];
+        let mut parts: BTreeMap<Part, Vec<_>> = BTreeMap::new();
+        for _ in 0..args.runs {
+            let report = solver(&input, &Part::selected(args.run.part))?;
+            parse.push(report.parse);
+            for part in report.parts {
+                parts.entry(part.part).or_default().push(part.elapsed);
+            }
+        }
+
+        let label = format!("{} day {}", year.year, day);
+        println!("{:<14} parse   {}", label, Summary::of(&parse).unwrap());
+        for (part, samples) in parts {
+            println!(
+                "{:<14} part {}  {}",
+                "",
+                part,
+                Summary::of(&samples).unwrap()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Read a program's source from `path`, or stdin for `-`
+fn read_source(path: &Path) -> anyhow::Result<String> {
+    if path.as_os_str() == "-" {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .context("could not read program from stdin")?;
+        Ok(source)
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("could not read program from {}", path.display()))
+    }
+}
+
+fn read_code(path: &Path) -> anyhow::Result<Vec<Val>> {
+    Program::parse_code(&read_source(path)?)
+}
+
+/// Write memory as comma-separated values, then any cells stored far past them as `addr=value`
+fn write_memory(memory: &Memory, out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "{}", memory.dense().iter().join(","))?;
+    let sparse = memory
+        .sparse()
+        .map(|(addr, val)| format!("{}={}", addr, val))
+        .join(",");
+    if !sparse.is_empty() {
+        writeln!(out, "sparse: {}", sparse)?;
+    }
+    Ok(())
+}
+
+pub fn disasm(args: &DisasmArgs) -> anyhow::Result<()> {
+    println!("{}", disasm::listing(&read_code(&args.file)?));
+    Ok(())
+}
+
+pub fn asm(args: &AsmArgs) -> anyhow::Result<()> {
+    let program = asm::assemble(&read_source(&args.file)?)?;
+    write_memory(&program.memory, &mut std::io::stdout())?;
+    Ok(())
+}
+
+pub fn debug(args: &DebugArgs) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        args.file.as_os_str() != "-",
+        "the debugger reads commands from stdin, so the program must come from a file"
+    );
+    let program = Program::my_parse(&read_source(&args.file)?)?;
+    Debugger::new(program).repl(std::io::stdin().lock(), std::io::stdout())
+}
+
+pub fn ascii(args: &AsciiArgs) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        args.file.as_os_str() != "-",
+        "input lines are read from stdin, so the program must come from a file"
+    );
+    let program = Program::my_parse(&read_source(&args.file)?)?;
+    let mut terminal = AsciiTerminal::new(program).live(std::io::stdin().lock());
+    if let Some(script) = &args.script {
+        for line in read_source(script)?.lines() {
+            terminal.send_line(line)?;
+        }
+    }
+    while let Some(output) = terminal.next_output()? {
+        println!("{}", output);
+    }
+    Ok(())
+}
+
+pub fn run_program(args: &ProgramArgs) -> anyhow::Result<()> {
+    let program = Program::my_parse(&read_source(&args.file)?)?;
+    let stdout = std::io::stdout().lock();
+    if !args.input.is_empty() {
+        return execute(args, program, &mut View::new(args.input.clone()), stdout);
+    }
+    anyhow::ensure!(
+        args.file.as_os_str() != "-",
+        "input is read from stdin, so the program must come from a file or `--input`"
+    );
+    let stdin = std::io::stdin();
+    let prompt = stdin.is_terminal();
+    let mut input = TextInput::new(stdin.lock(), args.in_format).prompt(prompt);
+    let result = execute(args, program, &mut input, stdout);
+    // bad input explains a program that then ran out of input
+    input.finish()?;
+    result
+}
+
+/// Patch and run `program` as `args` say, writing what it prints to `out`
+fn execute(
+    args: &ProgramArgs,
+    mut program: Program,
+    input: &mut impl TryReadVal,
+    mut out: impl Write,
+) -> anyhow::Result<()> {
+    for patch in &args.set {
+        program.memory[patch.addr] = patch.val;
+    }
+    let mut output = TextOutput::new(&mut out, args.out_format);
+    let result = program.eval_limited(input, &mut output, args.limits.limits());
+    output.finish()?;
+    let first = result?;
+    if args.result {
+        writeln!(out, "{}", first)?;
+    }
+    if args.dump {
+        write_memory(&program.memory, &mut out)?;
+    }
+    Ok(())
+}
+
+pub fn record_transcript(args: &TranscriptArgs) -> anyhow::Result<()> {
+    let mut program = Program::my_parse(&read_source(&args.file)?)?;
+    let mut input = if args.input.is_empty() {
+        let mut stdin = std::io::stdin();
+        View::stream(std::iter::from_fn(move || stdin.try_read_val()))
+    } else {
+        View::new(args.input.clone())
+    };
+    let log = File::create(&args.out)
+        .with_context(|| format!("could not create {}", args.out.display()))?;
+    let mut output = std::io::stdout();
+    let io = Joint {
+        input: &mut input,
+        output: &mut output,
+    };
+    let mut recording = Recording::new(io, log);
+    let result = program.eval_joint_limited(&mut recording, Limits::default());
+    recording.finish()?;
+    result?;
+    Ok(())
+}
+
+pub fn replay(args: &ReplayArgs) -> anyhow::Result<()> {
+    let mut program = Program::my_parse(&read_source(&args.file)?)?;
+    let transcript = read_source(&args.transcript)?
+        .parse()
+        .context("could not parse transcript")?;
+    let mut replay = Replay::new(transcript);
+    let result = program.eval_joint_limited(&mut replay, Limits::default());
+    // a mismatch stops the program, so it explains a failed run better than the run's own error
+    replay.finish()?;
+    result?;
+    println!("transcript matches");
+    Ok(())
+}
+
+pub fn trace(args: &TraceArgs) -> anyhow::Result<()> {
+    match args.word {
+        WordSize::I64 => trace_with::<i64>(args),
+        WordSize::I128 => trace_with::<Val>(args),
+        WordSize::Big => trace_with::<BigInt>(args),
+    }
+}
+
+fn trace_with<W: Word>(args: &TraceArgs) -> anyhow::Result<()> {
+    let mut machine = Machine::new(Program::<W>::my_parse(&read_source(&args.file)?)?)
+        .with_limits(args.limits.limits());
+    for val in &args.input {
+        machine.provide_input(
+            val.parse()
+                .with_context(|| format!("`{}` is not a valid input", val))?,
+        );
+    }
+    let writer = match &args.out {
+        Some(path) => {
+            let file = File::create(path)
+                .with_context(|| format!("could not create {}", path.display()))?;
+            Some(WriteTracer::new(BufWriter::new(file)))
+        }
+        None => None,
+    };
+
+    let mut tracer = (writer, Profile::default());
+    loop {
+        match machine.step_traced(&mut tracer)? {
+            Some(Event::Output(val)) => println!("{}", val),
+            Some(Event::Halted) => break,
+            Some(Event::NeedsInput) => anyhow::bail!("program needs more input than was given"),
+            None => {}
+        }
+    }
+    let (writer, profile) = tracer;
+    if let Some(writer) = writer {
+        writer.finish().context("could not write trace")?;
+    }
+    println!("{}", profile);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use clap::Parser;
+
+    use super::*;
+    use crate::cli::{Cli, Command, IntcodeCommand};
+
+    fn run_output(args: &[&str], program: &str) -> anyhow::Result<String> {
+        let cli = Cli::try_parse_from(["aoc", "intcode", "run", "prog.txt"].iter().chain(args))?;
+        let Command::Intcode {
+            command: IntcodeCommand::Run(args),
+        } = cli.command
+        else {
+            panic!("not an `intcode run` command");
+        };
+        let mut out = vec![];
+        let mut input = View::new(args.input.clone());
+        execute(&args, Program::my_parse(program)?, &mut input, &mut out)?;
+        Ok(String::from_utf8(out)?)
+    }
+
+    #[test]
+    fn test_run_program() {
+        // day 2's example, with its noun and verb patched to what they already are
+        let day_2 = "1,9,10,3,2,3,11,0,99,30,40,50";
+        assert_eq!(
+            run_output(&["--set", "1=9", "--set", "2=10", "--result"], day_2).unwrap(),
+            "3500\n"
+        );
+        assert_eq!(
+            run_output(&["--set", "1=10", "--result", "--dump"], day_2).unwrap(),
+            "4000\n4000,10,10,80,2,3,11,0,99,30,40,50\n"
+        );
+
+        // outputs 1 if the input is 8, otherwise 0
+        let equals_8 = "3,9,8,9,10,9,4,9,99,-1,8";
+        assert_eq!(run_output(&["--input", "8"], equals_8).unwrap(), "1\n");
+        assert_eq!(
+            run_output(&["--input=-8", "--out-format", "commas"], equals_8).unwrap(),
+            "0\n"
+        );
+        assert_eq!(
+            run_output(
+                &["--input", "8", "--set", "100000000000=3", "--dump"],
+                equals_8
+            )
+            .unwrap(),
+            "1\n3,9,8,9,10,9,4,9,99,1,8\nsparse: 100000000000=3\n"
+        );
+    }
+}
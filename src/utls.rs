@@ -1,33 +1,55 @@
 use std::str::FromStr;
 
+use anyhow::Context;
+
 use crate::program::Val;
 
 pub mod color;
 pub mod conversions;
 pub mod display;
 pub mod linalg;
+pub mod timing;
 
-pub fn read_text_from_file<T: MyParse>(year: &str, day: &str) -> T {
-    let str = std::fs::read_to_string(format!("inputs/{}-{}.txt", year, day)).unwrap();
-    T::my_parse(&str)
-}
-
-pub(crate) trait MyParse {
-    fn my_parse(s: &str) -> Self;
+pub(crate) trait MyParse: Sized {
+    fn my_parse(s: &str) -> anyhow::Result<Self>;
 }
 
 impl<T: FromStr> MyParse for Vec<T>
 where
-    <T as std::str::FromStr>::Err: std::fmt::Debug,
+    <T as std::str::FromStr>::Err: Into<anyhow::Error>,
 {
-    fn my_parse(s: &str) -> Vec<T> {
-        s.lines()
-            .filter(|x| !x.is_empty())
-            .map(|line| line.trim().parse().unwrap())
-            .collect()
+    fn my_parse(s: &str) -> anyhow::Result<Vec<T>> {
+        parse_lines(s, |line| line.parse().map_err(Into::into))
     }
 }
 
+/// Parse each non-empty line, reporting the (1-based) line number of the first failure
+pub fn parse_lines<T>(
+    s: &str,
+    mut parse: impl FnMut(&str) -> anyhow::Result<T>,
+) -> anyhow::Result<Vec<T>> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            parse(line.trim()).with_context(|| format!("line {}: `{}`", idx + 1, line.trim()))
+        })
+        .collect()
+}
+
+/// Parse a single line of decimal digits, reporting the (1-based) column of a bad character
+pub fn parse_digits(s: &str) -> anyhow::Result<Vec<u8>> {
+    s.trim()
+        .chars()
+        .enumerate()
+        .map(|(idx, c)| {
+            c.to_digit(10)
+                .map(|digit| digit as u8)
+                .with_context(|| format!("column {}: expected a digit, found `{}`", idx + 1, c))
+        })
+        .collect()
+}
+
 pub trait FromVal {
     fn from_val(val: Val) -> Self;
 }
@@ -3,7 +3,7 @@ use std::{collections::HashMap, fmt::Display};
 
 use super::linalg::Point;
 
-pub fn paint<T: Display + Copy + Default>(painting: &HashMap<Point, T>) {
+pub fn render<T: Display + Copy + Default>(painting: &HashMap<Point, T>) -> String {
     let (minx, maxx) = painting
         .keys()
         .map(|key| key.0)
@@ -17,17 +17,19 @@ pub fn paint<T: Display + Copy + Default>(painting: &HashMap<Point, T>) {
         .into_option()
         .unwrap();
     // using Euclidean plane, so big y -> last row in matrix grid
-    // also, printing row by row, so row-major order
-    for row in (miny..=maxy).rev() {
-        for col in minx..=maxx {
-            let color = painting
-                .get(&(col, row).into())
-                .copied()
-                // if unknown color, select black
-                .unwrap_or_default();
-
-            print!("{}", color);
-        }
-        println!();
-    }
+    // also, rendering row by row, so row-major order
+    (miny..=maxy)
+        .rev()
+        .map(|row| {
+            (minx..=maxx)
+                .map(|col| {
+                    painting
+                        .get(&(col, row).into())
+                        .copied()
+                        // if unknown color, select black
+                        .unwrap_or_default()
+                })
+                .join("")
+        })
+        .join("\n")
 }
@@ -0,0 +1,51 @@
+use std::time::{Duration, Instant};
+
+pub fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let res = f();
+    (res, start.elapsed())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Summary {
+    pub fn of(samples: &[Duration]) -> Option<Self> {
+        let mut samples = samples.to_vec();
+        samples.sort_unstable();
+        Some(Self {
+            min: *samples.first()?,
+            median: samples[samples.len() / 2],
+            max: *samples.last()?,
+        })
+    }
+}
+
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {:>10.2?}  median {:>10.2?}  max {:>10.2?}",
+            self.min, self.median, self.max
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let samples = [3, 1, 4, 2].map(Duration::from_millis);
+        let summary = Summary::of(&samples).unwrap();
+        assert_eq!(summary.min, Duration::from_millis(1));
+        assert_eq!(summary.median, Duration::from_millis(3));
+        assert_eq!(summary.max, Duration::from_millis(4));
+        assert_eq!(Summary::of(&[]), None);
+    }
+}
@@ -1,3 +1,8 @@
+use crate::{
+    program::Program,
+    puzzle::{DayReport, Part, Solution, Solver, Year, solve, solve_with},
+};
+
 mod day_01;
 mod day_02;
 mod day_03;
@@ -17,32 +22,60 @@ mod day_16;
 mod day_17;
 mod day_18;
 mod day_19;
+mod day_23;
+
+pub const YEAR: Year = Year {
+    year: 2019,
+    days: &[
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 23,
+    ],
+    solver,
+};
+
+fn solver(day: u32) -> Option<Solver> {
+    let solver = match day {
+        1 => solve::<day_01::Day01>,
+        2 => solve::<day_02::Day02>,
+        3 => solve::<day_03::Day03>,
+        4 => solve::<day_04::Day04>,
+        5 => solve::<day_05::Day05>,
+        6 => solve::<day_06::Day06>,
+        7 => solve::<day_07::Day07>,
+        8 => solve::<day_08::Day08>,
+        9 => solve::<day_09::Day09>,
+        10 => solve::<day_10::Day10>,
+        11 => solve::<day_11::Day11>,
+        12 => solve::<day_12::Day12>,
+        13 => solve::<day_13::Day13>,
+        14 => solve::<day_14::Day14>,
+        15 => solve::<day_15::Day15>,
+        16 => solve::<day_16::Day16>,
+        17 => solve::<day_17::Day17>,
+        18 => solve::<day_18::Day18>,
+        19 => solve::<day_19::Day19>,
+        23 => solve::<day_23::Day23>,
+        _ => return None,
+    };
+    Some(solver)
+}
 
-pub fn run() {
-    run_day(19);
+fn solve_cached<S: Solution<Input = Program>, const CACHE: bool>(
+    input: &str,
+    parts: &[Part],
+) -> anyhow::Result<DayReport> {
+    solve_with::<S>(input, parts, |program| program.with_decode_cache(CACHE))
 }
 
-fn run_day(day: i32) {
-    match day {
-        1 => day_01::run(),
-        2 => day_02::run(),
-        3 => day_03::run(),
-        4 => day_04::run(),
-        5 => day_05::run(),
-        6 => day_06::run(),
-        7 => day_07::run(),
-        8 => day_08::run(),
-        9 => day_09::run(),
-        10 => day_10::run(),
-        11 => day_11::run(),
-        12 => day_12::run(),
-        13 => day_13::run(),
-        14 => day_14::run(),
-        15 => day_15::run(),
-        16 => day_16::run(),
-        17 => day_17::run(),
-        18 => day_18::run(),
-        19 => day_19::run(),
-        _ => unreachable!("day not implemented"),
-    }
+/// The Intcode-heavy days, with the decode cache switched on or off
+pub fn intcode_solver(day: u32, decode_cache: bool) -> Option<Solver> {
+    let solver = match (day, decode_cache) {
+        (13, false) => solve_cached::<day_13::Day13, false>,
+        (13, true) => solve_cached::<day_13::Day13, true>,
+        (17, false) => solve_cached::<day_17::Day17, false>,
+        (17, true) => solve_cached::<day_17::Day17, true>,
+        (19, false) => solve_cached::<day_19::Day19, false>,
+        (19, true) => solve_cached::<day_19::Day19, true>,
+        _ => return None,
+    };
+    Some(solver)
 }
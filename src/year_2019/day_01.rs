@@ -1,3 +1,8 @@
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
+
 pub fn cost(x: i32) -> i32 {
     x / 3 - 2
 }
@@ -20,9 +25,20 @@ fn part_2(input: &[i32]) -> i32 {
     input.iter().map(|x| compute_total(*x)).sum()
 }
 
-pub fn run() {
-    let input: Vec<i32> = crate::utls::read_text_from_file("2019", "01");
-    part_1(&input);
-    let sol = part_2(&input);
-    println!("solution is {}", sol);
+pub struct Day01;
+
+impl Solution for Day01 {
+    type Input = Vec<i32>;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        MyParse::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input).into())
+    }
 }
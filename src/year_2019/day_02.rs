@@ -1,31 +1,46 @@
-use std::io;
+use crate::{
+    program::{Program, Val, io::infallible::Unused, limits::Limits},
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
 
-use crate::program::{Program, Val};
-
-#[allow(unused)]
-fn part_1(mut program: Program) -> Val {
+fn part_1(mut program: Program) -> anyhow::Result<Val> {
     program.init(12, 2);
-    program.eval(&mut io::stdin(), &mut io::stdout())
+    Ok(program.eval(&mut Unused, &mut Unused)?)
 }
 
-fn part_2(program: Program) -> Val {
+fn part_2(program: Program) -> anyhow::Result<Val> {
     const TARGET: Val = 19690720;
+    const STEP_LIMIT: u64 = 100_000;
     for noun in 0..100 {
         for verb in 0..100 {
-            println!("Noun: {}, verb: {}", noun, verb);
             let mut program = program.clone();
             program.init(noun, verb);
-            if program.eval(&mut std::io::stdin(), &mut std::io::stdout()) == TARGET {
-                return 100 * noun + verb;
+            // some inputs never halt; those can't be the answer
+            match program.eval_limited(&mut Unused, &mut Unused, Limits::steps(STEP_LIMIT)) {
+                Ok(TARGET) => return Ok(100 * noun + verb),
+                Err(err) if !err.kind.is_limit() => return Err(err.into()),
+                _ => {}
             }
         }
     }
-    unreachable!()
+    anyhow::bail!("no noun and verb produce {}", TARGET)
 }
 
-pub fn run() {
-    let input: Program = crate::utls::read_text_from_file("2019", "02");
-    // let res = part_1(input.clone());
-    let res = part_2(input);
-    println!("The answer is {}", res);
+pub struct Day02;
+
+impl Solution for Day02 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
 }
@@ -1,23 +1,33 @@
+use anyhow::Context;
 use itertools::Itertools;
 
-use crate::utls::MyParse;
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::{MyParse, parse_lines},
+};
 
 type Segment = (((i32, i32), i32), ((i32, i32), i32));
 
-// fn part_1(line1: Vec<String>, line2: Vec<String>) -> usize {
-//     let intersection = closest_intersection(intersections(line1, line2));
-//     manhattan_distance(intersection)
-// }
+fn part_1(line1: Vec<String>, line2: Vec<String>) -> anyhow::Result<i32> {
+    intersections(line1, line2)
+        .map(|(point, _)| point)
+        .filter(|point| *point != (0, 0))
+        .map(|point| manhattan_distance((0, 0), point))
+        .min()
+        .context("wires never intersect")
+}
 
 fn manhattan_distance(from: (i32, i32), to: (i32, i32)) -> i32 {
     (to.0 - from.0).abs() + (to.1 - from.1).abs()
 }
 
-fn closest_intersection(points: impl Iterator<Item = ((i32, i32), i32)>) -> ((i32, i32), i32) {
+fn closest_intersection(
+    points: impl Iterator<Item = ((i32, i32), i32)>,
+) -> anyhow::Result<((i32, i32), i32)> {
     points
         .filter(|(point, _)| *point != (0, 0))
         .min_by_key(|(_, dist)| *dist)
-        .unwrap()
+        .context("wires never intersect")
 }
 
 fn intersections(
@@ -85,7 +95,7 @@ fn walk(line: Vec<String>) -> impl Iterator<Item = Segment> + Clone {
             "L" => (-1, 0),
             "R" => (1, 0),
             "D" => (0, -1),
-            _ => unreachable!(),
+            _ => unreachable!("checked when parsing"),
         };
         let dst: i32 = num.parse().unwrap();
         let next = (fx + dx * dst, fy + dy * dst);
@@ -96,34 +106,47 @@ fn walk(line: Vec<String>) -> impl Iterator<Item = Segment> + Clone {
     std::iter::once(((0, 0), 0)).chain(points).tuple_windows()
 }
 
-#[allow(unused)]
-struct Lines(Vec<Vec<String>>);
+pub struct Lines(Vec<Vec<String>>);
 
 impl MyParse for Lines {
-    fn my_parse(s: &str) -> Self {
-        let inner = s
-            .trim()
-            .lines()
-            .map(|line| line.trim().split(",").map(|x| x.to_string()).collect())
-            .collect();
-        Self(inner)
+    fn my_parse(s: &str) -> anyhow::Result<Self> {
+        let inner = parse_lines(s, |line| {
+            line.split(",")
+                .map(|segment| {
+                    let valid = segment.split_at_checked(1).is_some_and(|(dir, num)| {
+                        ["U", "L", "R", "D"].contains(&dir) && num.parse::<i32>().is_ok()
+                    });
+                    anyhow::ensure!(valid, "`{}` is not a valid segment", segment);
+                    Ok(segment.to_string())
+                })
+                .collect()
+        })?;
+        anyhow::ensure!(inner.len() == 2, "expected 2 wires, found {}", inner.len());
+        Ok(Self(inner))
     }
 }
 
-fn part_2(line1: Vec<String>, line2: Vec<String>) -> i32 {
-    println!(
-        "The intersections: {:?}",
-        intersections(line1.clone(), line2.clone()).collect_vec()
-    );
-    let intersection = closest_intersection(intersections(line1, line2));
-    intersection.1
+fn part_2(line1: Vec<String>, line2: Vec<String>) -> anyhow::Result<i32> {
+    let intersection = closest_intersection(intersections(line1, line2))?;
+    Ok(intersection.1)
 }
 
-pub fn run() {
-    let Lines(input) = crate::utls::read_text_from_file("2019", "03");
-    let res = part_2(input[0].clone(), input[1].clone());
-    println!("The answer is {}", res);
-    // part_2(input);
+pub struct Day03;
+
+impl Solution for Day03 {
+    type Input = Lines;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Lines::my_parse(input)
+    }
+
+    fn part_1(Lines(input): &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input[0].clone(), input[1].clone())?.into())
+    }
+
+    fn part_2(Lines(input): &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input[0].clone(), input[1].clone())?.into())
+    }
 }
 
 #[cfg(test)]
@@ -136,8 +159,8 @@ U7,R6,D4,L4";
 
     #[test]
     fn test_solution() {
-        let Lines(input) = Lines::my_parse(INPUT);
-        let res = part_2(input[0].clone(), input[1].clone());
+        let Lines(input) = Lines::my_parse(INPUT).unwrap();
+        let res = part_2(input[0].clone(), input[1].clone()).unwrap();
         assert_eq!(res, 30);
     }
 
@@ -146,8 +169,8 @@ U7,R6,D4,L4";
         let input = r"
         R75,D30,R83,U83,L12,D49,R71,U7,L72
 U62,R66,U55,R34,D71,R55,D58,R83";
-        let Lines(input) = Lines::my_parse(input);
-        let res = part_2(input[0].clone(), input[1].clone());
+        let Lines(input) = Lines::my_parse(input).unwrap();
+        let res = part_2(input[0].clone(), input[1].clone()).unwrap();
         assert_eq!(res, 610);
     }
 
@@ -156,11 +179,25 @@ U62,R66,U55,R34,D71,R55,D58,R83";
         let input = r"
 R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51
 U98,R91,D20,R16,D67,R40,U7,R15,U6,R7";
-        let Lines(input) = Lines::my_parse(input);
-        let res = part_2(input[0].clone(), input[1].clone());
+        let Lines(input) = Lines::my_parse(input).unwrap();
+        let res = part_2(input[0].clone(), input[1].clone()).unwrap();
         assert_eq!(res, 410);
     }
 
+    #[test]
+    fn test_bad_wires() {
+        let Lines(input) = Lines::my_parse("R8,U5\nL3,D2").unwrap();
+        assert_eq!(
+            part_1(input[0].clone(), input[1].clone())
+                .unwrap_err()
+                .to_string(),
+            "wires never intersect"
+        );
+        assert!(part_2(input[0].clone(), input[1].clone()).is_err());
+        assert!(Lines::my_parse("R8,X5\nL3,D2").is_err());
+        assert!(Lines::my_parse("R8,U\nL3,D2").is_err());
+    }
+
     //     #[test]
     //     fn test_intersect() {
     //         let lhs = ((146, 53), (146, 4));
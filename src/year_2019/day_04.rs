@@ -1,46 +1,88 @@
+use anyhow::Context;
 use itertools::Itertools;
 
-use crate::utls::MyParse;
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
 
-fn is_password(num: i32) -> bool {
+/// `exact_pair` requires some run of repeated digits to be exactly two long
+fn is_password(num: i32, exact_pair: bool) -> bool {
     let str = num.to_string().chars().collect_vec();
 
     let runs = str.iter().chunk_by(|&&k| k);
-    let adjacent_repeat = runs.into_iter().any(|(_, run)| run.count() == 2);
+    let adjacent_repeat = runs.into_iter().any(|(_, run)| {
+        let count = run.count();
+        if exact_pair { count == 2 } else { count >= 2 }
+    });
     let monotonic_increase = str.iter().tuple_windows().all(|(l, r)| l <= r);
     adjacent_repeat && monotonic_increase
 }
 
-fn part_1(range: Range) -> usize {
+fn count_passwords(range: &Range, exact_pair: bool) -> usize {
     let lower_bound = std::cmp::max(range.lower, 100000);
     let upper_bound = std::cmp::min(range.upper, 999999);
     (lower_bound..=upper_bound)
-        .filter(|&num| is_password(num))
+        .filter(|&num| is_password(num, exact_pair))
         .count()
 }
 
-#[allow(unused)]
-fn part_2(input: &'static str) {}
+fn part_1(range: &Range) -> usize {
+    count_passwords(range, false)
+}
+
+fn part_2(range: &Range) -> usize {
+    count_passwords(range, true)
+}
 
-#[allow(unused)]
-struct Range {
+pub struct Range {
     lower: i32,
     upper: i32,
 }
 
 impl MyParse for Range {
-    fn my_parse(s: &str) -> Self {
-        let (left, right) = s.trim().split_once("-").unwrap();
-        Self {
-            lower: left.parse().unwrap(),
-            upper: right.parse().unwrap(),
-        }
+    fn my_parse(s: &str) -> anyhow::Result<Self> {
+        let (left, right) = s
+            .trim()
+            .split_once("-")
+            .context("expected `<lower>-<upper>`")?;
+        Ok(Self {
+            lower: left.parse().context("bad lower bound")?,
+            upper: right.parse().context("bad upper bound")?,
+        })
+    }
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    type Input = Range;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Range::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input).into())
     }
 }
 
-pub fn run() {
-    let input = crate::utls::read_text_from_file("2019", "04");
-    let res = part_1(input);
-    println!("The answer is {}", res);
-    // part_2(input);
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_passwords() {
+        assert!(is_password(111111, false));
+        assert!(!is_password(223450, false));
+        assert!(!is_password(123789, false));
+        assert!(is_password(112233, true));
+        assert!(!is_password(123444, true));
+        assert!(is_password(123444, false));
+        assert!(is_password(111122, true));
+    }
 }
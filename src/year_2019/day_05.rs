@@ -1,13 +1,43 @@
-use crate::program::{Program, Val};
+use anyhow::Context;
 
-fn part_1(mut program: Program) -> Val {
-    program.eval(&mut std::io::stdin(), &mut std::io::stdout())
+use crate::{
+    program::{Program, Val, io::View},
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
+
+/// Run the diagnostic program for `system_id`; the last output is the diagnostic code
+fn diagnostic(mut program: Program, system_id: Val) -> anyhow::Result<Val> {
+    let mut outputs: Vec<Val> = vec![];
+    program.eval(&mut View::new(vec![system_id]), &mut outputs)?;
+    outputs
+        .last()
+        .copied()
+        .context("program produced no output")
+}
+
+fn part_1(program: Program) -> anyhow::Result<Val> {
+    diagnostic(program, 1)
+}
+
+fn part_2(program: Program) -> anyhow::Result<Val> {
+    diagnostic(program, 5)
 }
 
-// fn part_2(input: &'static str) {}
+pub struct Day05;
+
+impl Solution for Day05 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
 
-pub fn run() {
-    let input = crate::utls::read_text_from_file("2019", "05");
-    part_1(input);
-    // part_2(input);
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
 }
@@ -1,11 +1,17 @@
-use std::{collections::HashMap, convert::Infallible, str::FromStr};
+use std::{collections::HashMap, str::FromStr};
 
+use anyhow::Context;
 use itertools::Itertools;
 
-// fn part_1(graph: Vec<Orbit>) -> usize {
-//     let dag = Dag::from_iter(graph);
-//     dag.count_orbits()
-// }
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
+
+fn part_1(graph: Vec<Orbit>) -> usize {
+    let dag = Dag::from_iter(graph);
+    dag.count_orbits()
+}
 
 fn part_2(graph: Vec<Orbit>) -> usize {
     let dag = Dag::from_iter(graph);
@@ -13,7 +19,8 @@ fn part_2(graph: Vec<Orbit>) -> usize {
     path.shortest_path("YOU", "SAN") - 2
 }
 
-struct Orbit {
+#[derive(Clone)]
+pub struct Orbit {
     from: String,
     to: String,
 }
@@ -67,24 +74,24 @@ impl Dag {
         "COM"
     }
 
-    // pub fn count_orbits(&self) -> usize {
-    //     let mut frontier = vec![self.root()];
-    //     let mut total_orbits = 0;
-    //     let mut level = 0;
-    //     while !frontier.is_empty() {
-    //         let mut new_frontier = vec![];
-    //         for cur in frontier {
-    //             total_orbits += level;
-    //             for nbor in self.neighbors(cur) {
-    //                 new_frontier.push(nbor.as_str());
-    //             }
-    //         }
-    //         frontier = new_frontier;
-    //         level += 1;
-    //     }
-
-    //     total_orbits
-    // }
+    pub fn count_orbits(&self) -> usize {
+        let mut frontier = vec![self.root()];
+        let mut total_orbits = 0;
+        let mut level = 0;
+        while !frontier.is_empty() {
+            let mut new_frontier = vec![];
+            for cur in frontier {
+                total_orbits += level;
+                for nbor in self.neighbors(cur) {
+                    new_frontier.push(nbor.as_str());
+                }
+            }
+            frontier = new_frontier;
+            level += 1;
+        }
+
+        total_orbits
+    }
 
     pub fn paths_from_root(&self) -> Path {
         let mut frontier = vec![(None, self.root())];
@@ -118,10 +125,13 @@ impl FromIterator<Orbit> for Dag {
 }
 
 impl FromStr for Orbit {
-    type Err = Infallible;
+    type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (from, to) = s.trim().split_once(")").unwrap();
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (from, to) = s
+            .trim()
+            .split_once(")")
+            .context("expected `<center>)<orbiter>`")?;
         Ok(Self {
             from: from.to_owned(),
             to: to.to_owned(),
@@ -129,16 +139,26 @@ impl FromStr for Orbit {
     }
 }
 
-pub fn run() {
-    let input = crate::utls::read_text_from_file("2019", "06");
-    let res = part_2(input);
-    println!("The result is: {}", res);
+pub struct Day06;
+
+impl Solution for Day06 {
+    type Input = Vec<Orbit>;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Vec::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone()).into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone()).into())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::utls::MyParse;
-
     use super::*;
 
     #[test]
@@ -157,7 +177,7 @@ J)K
 K)L
 K)YOU
 I)SAN";
-        let orbits = Vec::<Orbit>::my_parse(input);
+        let orbits = Vec::<Orbit>::my_parse(input).unwrap();
         let res = part_2(orbits);
         assert_eq!(res, 4);
     }
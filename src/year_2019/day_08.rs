@@ -1,70 +1,125 @@
+use anyhow::Context;
 use itertools::Itertools;
 
-use crate::utls::{MyParse, color::Color};
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::{MyParse, color::Color, parse_digits},
+};
 
 const WIDTH: usize = 25;
 const HEIGHT: usize = 6;
 
-fn part_1(layers: &[u8], width: usize, height: usize) -> usize {
+fn check_layers(layers: &[u8], width: usize, height: usize) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !layers.is_empty() && layers.len().is_multiple_of(width * height),
+        "{} pixels don't make whole {}x{} layers",
+        layers.len(),
+        width,
+        height
+    );
+    Ok(())
+}
+
+fn part_1(layers: &[u8], width: usize, height: usize) -> anyhow::Result<usize> {
+    check_layers(layers, width, height)?;
     let layers = layers.chunks(width * height);
     let min_zeroes = layers
         .min_by_key(|layer| layer.iter().filter(|&&x| x == 0).count())
-        .unwrap();
+        .context("no layers")?;
     let ones_count = min_zeroes.iter().filter(|&&x| x == 1).count();
     let twos_count = min_zeroes.iter().filter(|&&x| x == 2).count();
-    ones_count * twos_count
+    Ok(ones_count * twos_count)
 }
 
-fn part_2(layers: &[u8], width: usize, height: usize) {
-    let layers = layers
+fn part_2(layers: &[u8], width: usize, height: usize) -> anyhow::Result<String> {
+    check_layers(layers, width, height)?;
+    let layers: Vec<_> = layers
         .iter()
-        .map(|x| Color::try_from(*x).unwrap())
-        .collect_vec();
+        .map(|&x| Color::try_from(x).with_context(|| format!("`{}` is not a colour", x)))
+        .try_collect()?;
     let step = width * height;
-    for ridx in 0..height {
-        for cidx in 0..width {
-            let idx = ridx * width + cidx;
-            let color = layers[idx..]
-                .iter()
-                .step_by(step)
-                .find(|layer| layer != &&Color::Transparent)
-                .unwrap();
-
-            let to_print = if *color == Color::White { "⬜" } else { "⬛" };
-            print!("{}", to_print);
-        }
-        println!();
-    }
+    let rows: Vec<String> = (0..height)
+        .map(|ridx| {
+            (0..width)
+                .map(|cidx| {
+                    let idx = ridx * width + cidx;
+                    let color = layers[idx..]
+                        .iter()
+                        .step_by(step)
+                        .find(|layer| layer != &&Color::Transparent)
+                        .with_context(|| {
+                            format!("pixel ({}, {}) is transparent in every layer", cidx, ridx)
+                        })?;
+
+                    Ok(if *color == Color::White { "⬜" } else { "⬛" })
+                })
+                .collect::<anyhow::Result<String>>()
+        })
+        .try_collect()?;
+    Ok(rows.join("\n"))
 }
 
-#[allow(unused)]
-struct ParseMe(Vec<u8>);
+pub struct ParseMe(Vec<u8>);
 
 impl MyParse for ParseMe {
-    fn my_parse(s: &str) -> Self {
-        let nums = s.trim().chars().map(|c| c as u8 - b'0').collect();
-        Self(nums)
+    fn my_parse(s: &str) -> anyhow::Result<Self> {
+        Ok(Self(parse_digits(s)?))
     }
 }
 
-pub fn run() {
-    let ParseMe(input) = crate::utls::read_text_from_file("2019", "08");
-    let res = part_1(&input, WIDTH, HEIGHT);
-    part_2(&input, WIDTH, HEIGHT);
-    println!("The answer is {}", res);
+pub struct Day08;
+
+impl Solution for Day08 {
+    type Input = ParseMe;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        ParseMe::my_parse(input)
+    }
+
+    fn part_1(ParseMe(input): &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input, WIDTH, HEIGHT)?.into())
+    }
+
+    fn part_2(ParseMe(input): &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input, WIDTH, HEIGHT)?.into())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::utls::MyParse;
-
     use super::*;
 
     #[test]
     fn test_layers_d1_p1() {
         let input = "123456789012";
-        let ParseMe(input) = ParseMe::my_parse(input);
-        let res = part_1(&input, 2, 3);
+        let ParseMe(input) = ParseMe::my_parse(input).unwrap();
+        let res = part_1(&input, 2, 3).unwrap();
         assert_eq!(res, 1);
     }
+
+    #[test]
+    fn test_layers_d1_p2() {
+        let ParseMe(input) = ParseMe::my_parse("0222112222120000").unwrap();
+        let res = part_2(&input, 2, 2).unwrap();
+        assert_eq!(res, "⬛⬜\n⬜⬛");
+    }
+
+    #[test]
+    fn test_bad_image() {
+        let ParseMe(input) = ParseMe::my_parse("12345").unwrap();
+        assert_eq!(
+            part_1(&input, 2, 3).unwrap_err().to_string(),
+            "5 pixels don't make whole 2x3 layers"
+        );
+        let ParseMe(input) = ParseMe::my_parse("0232").unwrap();
+        assert_eq!(
+            part_2(&input, 2, 2).unwrap_err().to_string(),
+            "`3` is not a colour"
+        );
+        let ParseMe(input) = ParseMe::my_parse("02222122").unwrap();
+        assert_eq!(
+            part_2(&input, 2, 2).unwrap_err().to_string(),
+            "pixel (0, 1) is transparent in every layer"
+        );
+    }
 }
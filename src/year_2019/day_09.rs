@@ -1,42 +1,67 @@
-use crate::program::Program;
+use anyhow::Context;
 
-#[allow(unused)]
-fn part_1(mut program: Program) {
-    program.eval(&mut std::io::stdin(), &mut std::io::stdout());
+use crate::{
+    program::{Program, Val, io::View},
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
+
+/// Run BOOST in `mode`; it outputs any malfunctioning opcodes followed by the keycode
+fn boost(mut program: Program, mode: Val) -> anyhow::Result<Val> {
+    let mut outputs: Vec<Val> = vec![];
+    program.eval(&mut View::new(vec![mode]), &mut outputs)?;
+    outputs
+        .last()
+        .copied()
+        .context("program produced no output")
+}
+
+fn part_1(program: Program) -> anyhow::Result<Val> {
+    boost(program, 1)
 }
 
-fn part_2(mut program: Program) {
-    program.eval(&mut std::io::stdin(), &mut std::io::stdout());
+fn part_2(program: Program) -> anyhow::Result<Val> {
+    boost(program, 2)
 }
 
-pub fn run() {
-    let input: Program = crate::utls::read_text_from_file("2019", "09");
-    // part_1(input.clone());
-    part_2(input);
+pub struct Day09;
+
+impl Solution for Day09 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{program::Val, utls::MyParse};
-
     use super::*;
 
     #[test]
     fn test_solution() {
         let input = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
-        let mut program = Program::my_parse(input);
-        let mut buf = vec![];
-        program.eval(&mut std::io::stdin(), &mut buf);
-        assert_eq!(Program::parse_code(input), buf);
+        let mut program: Program = Program::my_parse(input).unwrap();
+        let mut buf: Vec<Val> = vec![];
+        program.eval(&mut std::io::stdin(), &mut buf).unwrap();
+        assert_eq!(Program::<Val>::parse_code(input).unwrap(), buf);
     }
 
     #[test]
 
     fn test_solution_2() {
         let input = "1102,34915192,34915192,7,4,7,99,0";
-        let mut program = Program::my_parse(input);
+        let mut program = Program::my_parse(input).unwrap();
         let mut buf: Vec<Val> = vec![];
-        program.eval(&mut std::io::stdin(), &mut buf);
+        program.eval(&mut std::io::stdin(), &mut buf).unwrap();
         assert!(buf.len() == 1);
         assert!(buf[0].to_string().len() == 16);
     }
@@ -45,9 +70,9 @@ mod test {
 
     fn test_solution_3() {
         let input = "104,1125899906842624,99";
-        let mut program = Program::my_parse(input);
+        let mut program = Program::my_parse(input).unwrap();
         let mut buf: Vec<Val> = vec![];
-        program.eval(&mut std::io::stdin(), &mut buf);
+        program.eval(&mut std::io::stdin(), &mut buf).unwrap();
         assert!(buf.len() == 1);
         assert_eq!(buf[0], 1125899906842624);
     }
@@ -6,9 +6,12 @@ use std::{
 use fraction::GenericFraction;
 use itertools::Itertools;
 
-use crate::maze::Maze;
+use crate::{
+    maze::Maze,
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
 
-#[allow(unused)]
 fn part_1(maze: &Maze) -> usize {
     let points = maze.points.iter().map(|&x| x.into()).collect_vec();
     let lines = lines(&points);
@@ -53,15 +56,11 @@ fn part_2(maze: &Maze, nth: usize) -> Point {
     let mut lhs: Vec<_> = lhs.into_iter().map(|line| line.into_iter()).collect();
     let mut rhs: Vec<_> = rhs.into_iter().map(|line| line.into_iter()).collect();
     let mut count = 0;
-    let mut destroyed = vec![];
 
-    maze.print_with_destroyed(best_point.into(), &destroyed);
     for _ in 0..500 {
         for line in rhs.iter_mut().chain(lhs.iter_mut()) {
             if let Some(nxt) = line.next() {
                 count += 1;
-                destroyed.push(nxt.into());
-                maze.print_with_destroyed(best_point.into(), &destroyed);
                 if count == nth {
                     return nxt;
                 }
@@ -171,18 +170,27 @@ fn counts(points_on_lines: &PointsOnLines) -> HashMap<Point, usize> {
     counts
 }
 
-pub fn run() {
-    let maze: Maze = crate::utls::read_text_from_file("2019", "10");
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Maze;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Maze::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input).into())
+    }
 
-    let res = part_2(&maze.clone(), 200);
-    let points: (i32, i32) = res.into();
-    println!("The answer is {}", points.0 * 100 + points.1);
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        let points: (i32, i32) = part_2(input, 200).into();
+        Ok((points.0 * 100 + points.1).into())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::utls::MyParse;
-
     use super::*;
 
     #[test]
@@ -194,7 +202,7 @@ mod test {
 ....#
 ...##
 ";
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let ans = part_1(&maze);
         assert_eq!(ans, 8);
     }
@@ -212,7 +220,7 @@ mod test {
 .##.#..###
 ##...#..#.
 .#....####";
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let ans = part_1(&maze);
         assert_eq!(ans, 33);
     }
@@ -230,7 +238,7 @@ mod test {
 ..##....##
 ......#...
 .####.###.";
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let ans = part_1(&maze);
         assert_eq!(ans, 35);
     }
@@ -248,7 +256,7 @@ mod test {
 #..#.#.###
 .##...##.#
 .....#.#..";
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let ans = part_1(&maze);
         assert_eq!(ans, 41);
     }
@@ -276,7 +284,7 @@ mod test {
 .#.#.###########.###
 #.#.#.#####.####.###
 ###.##.####.##.#..##";
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let ans = part_1(&maze);
         assert_eq!(ans, 210);
     }
@@ -303,7 +311,7 @@ mod test {
 .#.#.###########.###
 #.#.#.#####.####.###
 ###.##.####.##.#..##";
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         assert_eq!(part_2(&maze, 1), (11, 12).into());
         assert_eq!(part_2(&maze, 2), (12, 1).into());
         assert_eq!(part_2(&maze, 3), (12, 2).into());
@@ -324,7 +332,7 @@ mod test {
 ##...#...#.#####.
 ..#.....#...###..
 ..#.#.....#....##";
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         part_2(&maze, 20);
     }
 }
@@ -1,30 +1,72 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::mpsc::{RecvTimeoutError, TryRecvError},
+    time::Duration,
+};
+
+use anyhow::Context;
 
 use crate::{
-    program::{Program, io::TryWriteVal, spawn},
+    program::{
+        Program, Val,
+        io::TryWriteVal,
+        spawn::{self, ProgramHandle, Status},
+    },
+    puzzle::{Answer, Solution},
     utls::{
+        MyParse,
         color::Color,
-        display::paint,
+        display::render,
         linalg::{Point, RotateDir},
     },
 };
 
-fn part_1(program: Program) -> usize {
-    let points = paint_robot(program, Color::Black);
-    points.len()
+fn part_1(program: Program) -> anyhow::Result<usize> {
+    let points = paint_robot(program, Color::Black)?;
+    Ok(points.len())
 }
 
-fn paint_robot(program: Program, start_color: Color) -> HashMap<Point, Color> {
+/// How often the robot checks whether its brain is stuck; only affects how quickly a stall is
+/// noticed, not whether it is
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The brain's next output, or `None` once it has stopped
+fn next_output(brain: &ProgramHandle) -> anyhow::Result<Option<Val>> {
+    loop {
+        match brain.recv_timeout(POLL_INTERVAL) {
+            Ok(val) => return Ok(Some(val)),
+            // `join` reports whether it halted or faulted
+            Err(RecvTimeoutError::Disconnected) => return Ok(None),
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        // anything sent before the status changed is already waiting to be received
+        let status = brain.status();
+        match brain.try_recv() {
+            Ok(val) => return Ok(Some(val)),
+            Err(TryRecvError::Disconnected) => return Ok(None),
+            Err(TryRecvError::Empty) => {}
+        }
+        match status {
+            Status::Running => {}
+            Status::WaitingForInput => {
+                anyhow::bail!("robot brain wants input before it has finished moving")
+            }
+            Status::Halted | Status::Faulted => return Ok(None),
+        }
+    }
+}
+
+fn paint_robot(program: Program, start_color: Color) -> anyhow::Result<HashMap<Point, Color>> {
     let mut points: HashMap<Point, Color> = HashMap::new();
     let mut dir = Point::UP;
     let mut pos = Point::default();
     let mut program = spawn::spawn(program);
     program.try_write_val(start_color as i128);
-    while let Some(paint_color) = program.try_read_val() {
+    while let Some(paint_color) = next_output(&program)? {
         points.insert(pos, u8::try_from(paint_color).unwrap().try_into().unwrap());
         let turn_dir = {
-            let raw_dir = u8::try_from(program.try_read_val().unwrap()).unwrap();
-            RotateDir::try_from(raw_dir).unwrap()
+            let raw_dir = next_output(&program)?.context("robot brain stopped mid-move")?;
+            RotateDir::try_from(u8::try_from(raw_dir).unwrap()).unwrap()
         };
         dir = dir.rotate(turn_dir);
         pos += dir;
@@ -32,22 +74,49 @@ fn paint_robot(program: Program, start_color: Color) -> HashMap<Point, Color> {
         let new_pos_color = points.get(&pos).copied().unwrap_or_default();
         program.try_write_val(new_pos_color as i128);
     }
-    program.join();
-    points
+    program.join()?;
+    Ok(points)
 }
 
-fn part_2(program: Program) {
-    let painting = paint_robot(program, Color::White);
-    // figure out painting bounds
-    paint(&painting);
+fn part_2(program: Program) -> anyhow::Result<String> {
+    let painting = paint_robot(program, Color::White)?;
+    Ok(render(&painting))
 }
 
-pub fn run() {
-    let input: Program = crate::utls::read_text_from_file("2019", "11");
-    let res = part_1(input.clone());
-    println!("Answer is {}", res);
-    part_2(input);
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stall() {
+        // reads the colour under it, then wants more input instead of moving
+        let program: Program = Program::my_parse("3,0,3,0,99").unwrap();
+        let err = paint_robot(program, Color::Black).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "robot brain wants input before it has finished moving"
+        );
+
+        // paints, turns and halts
+        let program: Program = Program::my_parse("3,0,104,1,104,0,99").unwrap();
+        assert_eq!(paint_robot(program, Color::Black).unwrap().len(), 1);
+    }
+}
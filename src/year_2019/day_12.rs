@@ -1,9 +1,13 @@
-use std::{collections::HashSet, convert::Infallible, str::FromStr};
+use std::{collections::HashSet, str::FromStr};
 
+use anyhow::Context;
 use itertools::Itertools;
 use regex::Regex;
 
-use crate::utls::linalg::Point3D;
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::{MyParse, linalg::Point3D},
+};
 
 fn part_1(points: Vec<Point3D>) -> i32 {
     let points: (Vec<i32>, _, _) = points
@@ -82,29 +86,45 @@ fn part_2(points: Vec<Point3D>) -> usize {
     loop_lens.into_iter().reduce(num::integer::lcm).unwrap()
 }
 
-pub fn run() {
-    let input: Vec<Point3D> = crate::utls::read_text_from_file("2019", "12");
-    let res = part_1(input.clone());
-    println!("The answer is {}", res);
-    let res = part_2(input);
-    println!("The answer is {}", res);
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Vec<Point3D>;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        MyParse::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone()).into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone()).into())
+    }
 }
 
 impl FromStr for Point3D {
-    type Err = Infallible;
+    type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> anyhow::Result<Self> {
         let regex = Regex::new(r"<x=([-\d]+), y=([-\d]+), z=([-\d]+)>").unwrap();
 
-        let (x, y, z) = regex
+        let captures = regex
             .captures(s)
-            .unwrap()
+            .context("expected `<x=<int>, y=<int>, z=<int>>`")?;
+        let (x, y, z) = captures
             .iter()
             .skip(1)
-            .map(|m| m.unwrap().as_str().parse().unwrap())
+            .map(|m| {
+                let m = m.unwrap();
+                m.as_str()
+                    .parse()
+                    .with_context(|| format!("column {}: bad coordinate", m.start() + 1))
+            })
             .collect_tuple()
             .unwrap();
 
-        Ok(Self(x, y, z))
+        Ok(Self(x?, y?, z?))
     }
 }
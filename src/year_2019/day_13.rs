@@ -6,9 +6,11 @@ use num_enum::TryFromPrimitive;
 use crate::{
     program::{
         Program, Val,
-        io::{TryReadVal, TryWriteVal},
+        io::{TryReadVal, TryWriteVal, infallible::Unused},
+        limits::Limits,
     },
-    utls::{display::paint, linalg::Point},
+    puzzle::{Answer, Solution},
+    utls::{MyParse, linalg::Point},
 };
 
 #[derive(Copy, Clone, Default, TryFromPrimitive, Eq, PartialEq, derive_more::Display)]
@@ -26,10 +28,10 @@ pub enum ArcadeTile {
     #[display("o")]
     Ball = 4,
 }
-fn part_1(mut program: Program) -> usize {
+fn part_1(mut program: Program) -> anyhow::Result<usize> {
     let mut output_buf: Vec<i128> = vec![];
 
-    program.eval(&mut std::io::stdin(), &mut output_buf);
+    program.eval(&mut Unused, &mut output_buf)?;
 
     let map: HashMap<Point, ArcadeTile> = output_buf
         .into_iter()
@@ -42,11 +44,10 @@ fn part_1(mut program: Program) -> usize {
         })
         .collect();
 
-    paint(&map);
-
-    map.values()
+    Ok(map
+        .values()
         .filter(|value| **value == ArcadeTile::Block)
-        .count()
+        .count())
 }
 
 #[derive(Default)]
@@ -60,10 +61,8 @@ impl ArcadeProgram {
     pub fn new() -> Self {
         Self::default()
     }
-}
 
-impl TryReadVal for ArcadeProgram {
-    fn try_read_val(&mut self) -> Option<Val> {
+    fn joystick(&self) -> Val {
         // move paddle toward ball
         let ball_pos = self
             .game_state
@@ -79,12 +78,10 @@ impl TryReadVal for ArcadeProgram {
             .0;
 
         // only need to consider x dimension
-        Some((ball_pos.0 - paddle_pos.0).signum() as Val)
+        (ball_pos.0 - paddle_pos.0).signum() as Val
     }
-}
 
-impl TryWriteVal for ArcadeProgram {
-    fn try_write_val(&mut self, val: crate::program::Val) -> Option<()> {
+    fn receive(&mut self, val: Val) {
         self.new_tile_buf.push(val);
 
         if self.new_tile_buf.len() == 3 {
@@ -104,21 +101,110 @@ impl TryWriteVal for ArcadeProgram {
             }
             self.new_tile_buf.clear();
         }
+    }
+}
+
+impl TryReadVal for ArcadeProgram {
+    fn try_read_val(&mut self) -> Option<Val> {
+        Some(self.joystick())
+    }
+}
+
+impl TryWriteVal for ArcadeProgram {
+    fn try_write_val(&mut self, val: Val) -> Option<()> {
+        self.receive(val);
         Some(())
     }
 }
 
-fn part_2(mut program: Program) -> usize {
-    program.code[0] = 2;
+fn part_2(mut program: Program) -> anyhow::Result<usize> {
+    program.memory[0] = 2;
     let mut arcade_program = ArcadeProgram::new();
-    program.eval_joint(&mut arcade_program);
-    arcade_program.score
+    program.eval_joint_limited(&mut arcade_program, Limits::default())?;
+    Ok(arcade_program.score)
 }
 
-pub fn run() {
-    let input: Program = crate::utls::read_text_from_file("2019", "13");
-    let res = part_1(input.clone());
-    println!("The answer is {}", res);
-    let res = part_2(input);
-    println!("The answer is {}", res);
+pub struct Day13;
+
+impl Solution for Day13 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        asm::assemble,
+        io::transcript::{Recording, Replay},
+    };
+
+    /// Draws a paddle to the left of the ball, then scores 10 plus the joystick position
+    fn game() -> Program {
+        assemble(
+            "
+                    add [free], [free], [free]
+                    out 0
+                    out 1
+                    out 3
+                    out 2
+                    out 0
+                    out 4
+                    in [joystick]
+                    add [joystick], 10, [score]
+                    out -1
+                    out 0
+                    out [score]
+                    hlt
+            free:   data 0
+            joystick: data 0
+            score:  data 0
+            ",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_replay_game() {
+        assert_eq!(part_2(game()).unwrap(), 11);
+
+        let mut log = vec![];
+        let mut recording = Recording::new(ArcadeProgram::new(), &mut log);
+        game()
+            .eval_joint_limited(&mut recording, Limits::default())
+            .unwrap();
+        assert_eq!(recording.finish().unwrap().score, 11);
+        let transcript = String::from_utf8(log).unwrap();
+        assert_eq!(
+            transcript.lines().join(","),
+            "out 0,out 1,out 3,out 2,out 0,out 4,in 1,out -1,out 0,out 11"
+        );
+
+        let mut replay = Replay::new(transcript.parse().unwrap());
+        game()
+            .eval_joint_limited(&mut replay, Limits::default())
+            .unwrap();
+        replay.finish().unwrap();
+
+        // steering the other way no longer matches the recorded score
+        let mut replay = Replay::new(transcript.replace("in 1", "in -1").parse().unwrap());
+        game()
+            .eval_joint_limited(&mut replay, Limits::default())
+            .unwrap_err();
+        assert_eq!(
+            replay.finish().unwrap_err().to_string(),
+            "entry 10: expected `out 11`, found `out 9`"
+        );
+    }
 }
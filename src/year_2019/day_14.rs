@@ -1,7 +1,13 @@
-use std::{collections::HashMap, convert::Infallible, str::FromStr};
+use std::{collections::HashMap, str::FromStr};
 
+use anyhow::Context;
 use itertools::Itertools;
 
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
+
 fn part_1(relations: &HashMap<String, Relation>) -> usize {
     fuel_to_ore(relations, 1)
 }
@@ -77,67 +83,73 @@ fn part_2(relations: &HashMap<String, Relation>) -> usize {
         }
     }
 
-    println!(
-        "Sanity check: {} gives us {}, and {} gives us {}\nSanity check: {} gives us {}, and {} gives us {}",
-        low,
-        fuel_to_ore(relations, low),
-        low - 1,
-        fuel_to_ore(relations, low - 1),
-        low,
-        1000000000000usize,
-        low - 1,
-        1000000000000usize
-    );
-
     low - 1
 }
 
 #[derive(Clone)]
-struct Relation {
+pub struct Relation {
     inputs: Vec<(usize, String)>,
     name: String,
     factor: usize,
 }
 
+/// Parse a `<count> <chemical>` term
+fn parse_term(term: &str) -> anyhow::Result<(usize, String)> {
+    let (count, chemical) = term
+        .trim()
+        .split_once(" ")
+        .with_context(|| format!("expected `<count> <chemical>`, found `{}`", term.trim()))?;
+    let count = count
+        .trim()
+        .parse()
+        .with_context(|| format!("bad count in `{}`", term.trim()))?;
+    Ok((count, chemical.trim().to_string()))
+}
+
 impl FromStr for Relation {
-    type Err = Infallible;
+    type Err = anyhow::Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn from_str(s: &str) -> anyhow::Result<Self> {
         // 44 XJWVT, 5 KHKGT, 1 QDVJ, 29 NZVS, 9 GPVTF, 48 HKGWZ => 1 FUEL
-        let (input, output) = s.trim().split_once(" => ").unwrap();
-        let (count, output) = output.trim().split_once(" ").unwrap();
-        let inputs = input
-            .split(", ")
-            .map(|input| {
-                let (count, input) = input.trim().split_once(" ").unwrap();
-                (count.trim().parse().unwrap(), input.trim().to_string())
-            })
-            .collect_vec();
+        let (input, output) = s
+            .trim()
+            .split_once(" => ")
+            .context("expected `<inputs> => <output>`")?;
+        let (factor, name) = parse_term(output)?;
+        let inputs = input.split(", ").map(parse_term).try_collect()?;
 
         Ok(Self {
             inputs,
-            name: output.trim().to_string(),
-            factor: count.trim().parse().unwrap(),
+            name,
+            factor,
         })
     }
 }
 
-pub fn run() {
-    let input: Vec<Relation> = crate::utls::read_text_from_file("2019", "14");
-    let input = input
-        .into_iter()
-        .map(|relation| (relation.name.clone(), relation))
-        .collect();
-    let res = part_1(&input);
-    println!("The answer is {}", res);
-    let res = part_2(&input);
-    println!("The answer is {}", res);
+pub struct Day14;
+
+impl Solution for Day14 {
+    type Input = HashMap<String, Relation>;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        let input: Vec<Relation> = MyParse::my_parse(input)?;
+        Ok(input
+            .into_iter()
+            .map(|relation| (relation.name.clone(), relation))
+            .collect())
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input).into())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::utls::MyParse;
-
     use super::*;
 
     #[test]
@@ -149,7 +161,7 @@ mod test {
 7 A, 1 C => 1 D
 7 A, 1 D => 1 E
 7 A, 1 E => 1 FUEL";
-        let input: Vec<Relation> = Vec::my_parse(input);
+        let input: Vec<Relation> = Vec::my_parse(input).unwrap();
         let input = input
             .into_iter()
             .map(|relation| (relation.name.clone(), relation))
@@ -167,7 +179,7 @@ mod test {
 5 B, 7 C => 1 BC
 4 C, 1 A => 1 CA
 2 AB, 3 BC, 4 CA => 1 FUEL";
-        let input: Vec<Relation> = Vec::my_parse(input);
+        let input: Vec<Relation> = Vec::my_parse(input).unwrap();
         let input = input
             .into_iter()
             .map(|relation| (relation.name.clone(), relation))
@@ -187,7 +199,7 @@ mod test {
 7 DCFZ, 7 PSHF => 2 XJWVT
 165 ORE => 2 GPVTF
 3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT";
-        let input: Vec<Relation> = Vec::my_parse(input);
+        let input: Vec<Relation> = Vec::my_parse(input).unwrap();
         let input = input
             .into_iter()
             .map(|relation| (relation.name.clone(), relation))
@@ -210,7 +222,7 @@ mod test {
 1 NVRVD => 8 CXFTF
 1 VJHF, 6 MNCFX => 4 RFSQX
 176 ORE => 6 VJHF";
-        let input: Vec<Relation> = Vec::my_parse(input);
+        let input: Vec<Relation> = Vec::my_parse(input).unwrap();
         let input = input
             .into_iter()
             .map(|relation| (relation.name.clone(), relation))
@@ -238,7 +250,7 @@ mod test {
 121 ORE => 7 VRPVC
 7 XCVML => 6 RJRHP
 5 BHXH, 4 VRPVC => 5 LTCX";
-        let input: Vec<Relation> = Vec::my_parse(input);
+        let input: Vec<Relation> = Vec::my_parse(input).unwrap();
         let input = input
             .into_iter()
             .map(|relation| (relation.name.clone(), relation))
@@ -258,7 +270,7 @@ mod test {
 7 DCFZ, 7 PSHF => 2 XJWVT
 165 ORE => 2 GPVTF
 3 DCFZ, 7 NZVS, 5 HKGWZ, 10 PSHF => 8 KHKGT";
-        let input: Vec<Relation> = Vec::my_parse(input);
+        let input: Vec<Relation> = Vec::my_parse(input).unwrap();
         let input = input
             .into_iter()
             .map(|relation| (relation.name.clone(), relation))
@@ -266,4 +278,15 @@ mod test {
         let res = part_2(&input);
         assert_eq!(res, 82892753);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let err = Vec::<Relation>::my_parse("10 ORE => 10 A\n7 A, B => 1 C")
+            .err()
+            .unwrap();
+        assert_eq!(
+            format!("{:#}", err),
+            "line 2: `7 A, B => 1 C`: expected `<count> <chemical>`, found `B`"
+        );
+    }
 }
@@ -1,42 +1,35 @@
 use std::collections::{HashMap, HashSet, hash_map::Entry};
 
+use anyhow::Context;
 use num_enum::TryFromPrimitive;
 use strum::IntoEnumIterator;
 
 use crate::{
     program::{
         Program, Val,
-        io::TryWriteVal,
-        spawn::{self, ProgramHandle},
+        machine::{Event, Machine},
     },
+    puzzle::{Answer, Solution},
     utls::{
-        ValInto,
-        color::Color,
-        display,
+        MyParse, ValInto,
         linalg::{CardinalDir, Point},
     },
 };
 
-struct DfsCtx<'a> {
+#[derive(Default)]
+struct DfsCtx {
     visited: HashMap<Point, usize>,
-    program_handle: &'a mut ProgramHandle,
     oxygen_system: Option<Point>,
 }
 
-impl<'a> DfsCtx<'a> {
-    pub fn new(program_handle: &'a mut ProgramHandle) -> Self {
-        Self {
-            visited: Default::default(),
-            program_handle,
-            oxygen_system: None,
-        }
-    }
-    pub fn dfs(&mut self, depth: usize, point: Point) {
+impl DfsCtx {
+    /// Explore from `point`, where `droid` is paused waiting for its next move
+    pub fn dfs(&mut self, depth: usize, point: Point, droid: &mut Machine) -> anyhow::Result<()> {
         match self.visited.entry(point) {
             Entry::Occupied(mut occupied_entry) => {
                 let best_depth = occupied_entry.get_mut();
                 if *best_depth <= depth {
-                    return;
+                    return Ok(());
                 }
 
                 *best_depth = depth;
@@ -46,18 +39,16 @@ impl<'a> DfsCtx<'a> {
             }
         }
 
-        // // we don't need to continue our recurse if our current point is the oxygen system
-        // if self.oxygen_system == Some(point) {
-        //     return;
-        // }
-
-        // if we got here, then we found a new best path
+        // if we got here, then we found a new best path; try each direction from here
+        let here = droid.snapshot();
         for dir in CardinalDir::iter() {
             let new_pos = point + dir.into();
-            // step forwawrd
-            self.program_handle.try_write_val(dir as Val);
-            // check move
-            let move_res: DroidState = self.program_handle.try_read_val().unwrap().val_into();
+            droid.restore(&here);
+            droid.provide_input(dir as Val);
+            let move_res: DroidState = match droid.run()? {
+                Event::Output(status) => status.val_into(),
+                event => anyhow::bail!("droid stopped with {:?} instead of reporting", event),
+            };
             match move_res {
                 DroidState::Wall => {
                     continue;
@@ -67,39 +58,32 @@ impl<'a> DfsCtx<'a> {
                     self.oxygen_system = Some(new_pos);
                 }
             }
-            // recurse dfs
-            self.dfs(depth + 1, point + dir.into());
-            // Undo
-            self.program_handle.try_write_val(-dir as Val);
-            // we know that this will succeed
-            self.program_handle.try_read_val();
+            self.dfs(depth + 1, new_pos, droid)?;
         }
+        Ok(())
     }
 }
 
-// fn part_1(program: Program) -> usize {
-//     let mut program_handle = spawn::spawn(program);
-//     let mut dfs_ctx = DfsCtx::new(&mut program_handle);
-//     dfs_ctx.dfs(0, Point::default());
-//     let oxygen_point = dfs_ctx.oxygen_system.unwrap();
-//     dfs_ctx.visited[&oxygen_point]
-// }
-
-fn part_2(program: Program) -> usize {
-    let mut program_handle = spawn::spawn(program);
-    let mut dfs_ctx = DfsCtx::new(&mut program_handle);
-    dfs_ctx.dfs(0, Point::default());
-    let oxygen_point = dfs_ctx.oxygen_system.unwrap();
+fn explore(program: Program) -> anyhow::Result<(DfsCtx, Point)> {
+    let mut dfs_ctx = DfsCtx::default();
+    dfs_ctx.dfs(0, Point::default(), &mut Machine::new(program))?;
+    let oxygen_point = dfs_ctx
+        .oxygen_system
+        .context("droid never found the oxygen system")?;
+    Ok((dfs_ctx, oxygen_point))
+}
+
+fn part_1(program: Program) -> anyhow::Result<usize> {
+    let (dfs_ctx, oxygen_point) = explore(program)?;
+    Ok(dfs_ctx.visited[&oxygen_point])
+}
+
+fn part_2(program: Program) -> anyhow::Result<usize> {
+    let (dfs_ctx, oxygen_point) = explore(program)?;
     let mut level = vec![oxygen_point];
 
     let mut visited = HashSet::new();
     let mut real_depth = 0;
-    let paintable: HashMap<_, Color> = dfs_ctx
-        .visited
-        .keys()
-        .map(|point| (*point, Color::White))
-        .collect();
-    display::paint(&paintable);
     while !level.is_empty() {
         let mut new_level = vec![];
         let mut level_effective_nonempty = false;
@@ -120,7 +104,7 @@ fn part_2(program: Program) -> usize {
             real_depth += 1;
         }
     }
-    real_depth - 1
+    Ok(real_depth - 1)
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, TryFromPrimitive)]
@@ -131,9 +115,113 @@ enum DroidState {
     OxygenSystem = 2,
 }
 
-pub fn run() {
-    let input: Program = crate::utls::read_text_from_file("2019", "15");
-    // let res = part_1(input.clone());
-    let res = part_2(input);
-    println!("The answer is {}", res);
+pub struct Day15;
+
+impl Solution for Day15 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::{
+        asm::assemble,
+        io::{
+            Joint, View,
+            transcript::{self, Recording, Replay, Transcript},
+        },
+        limits::Limits,
+    };
+
+    /// A corridor two steps long running east, with the oxygen system at its end; moving
+    /// `0` switches the droid off
+    fn corridor() -> Program {
+        assemble(
+            "
+            loop:   in [dir]
+                    jf [dir], stop
+                    eq [dir], 4, [t]
+                    jt [t], east
+                    eq [dir], 3, [t]
+                    jt [t], west
+            wall:   out 0
+                    jt 1, loop
+            east:   eq [x], 2, [t]
+                    jt [t], wall
+                    add [x], 1, [x]
+                    jt 1, moved
+            west:   jf [x], wall
+                    add [x], -1, [x]
+            moved:  eq [x], 2, [t]
+                    jt [t], found
+                    out 1
+                    jt 1, loop
+            found:  out 2
+                    jt 1, loop
+            stop:   hlt
+            dir:    data 0
+            t:      data 0
+            x:      data 0
+            ",
+        )
+        .unwrap()
+    }
+
+    /// Drive the droid with `moves`, returning the transcript of the session
+    fn record(moves: Vec<Val>) -> Transcript {
+        let mut input = View::new(moves);
+        let mut output: Vec<Val> = vec![];
+        let io = Joint {
+            input: &mut input,
+            output: &mut output,
+        };
+        let mut log = vec![];
+        let mut recording = Recording::new(io, &mut log);
+        corridor()
+            .eval_joint_limited(&mut recording, Limits::default())
+            .unwrap();
+        recording.finish().unwrap();
+        String::from_utf8(log).unwrap().parse().unwrap()
+    }
+
+    fn replay(transcript: Transcript) -> anyhow::Result<()> {
+        let mut replay = Replay::new(transcript);
+        let result = corridor().eval_joint_limited(&mut replay, Limits::default());
+        replay.finish()?;
+        result?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_droid() {
+        assert_eq!(part_1(corridor()).unwrap(), 2);
+        assert_eq!(part_2(corridor()).unwrap(), 2);
+
+        let transcript = record(vec![3, 4, 4, 4, 0]);
+        assert_eq!(
+            transcript.to_string().replace('\n', ","),
+            "in 3,out 0,in 4,out 1,in 4,out 2,in 4,out 0,in 0"
+        );
+        replay(transcript.clone()).unwrap();
+
+        // a wall where the recording had open floor
+        let mut moved = transcript;
+        moved.0[1] = transcript::Entry::Out(1);
+        assert_eq!(
+            replay(moved).unwrap_err().to_string(),
+            "entry 2: expected `out 1`, found `out 0`"
+        );
+    }
 }
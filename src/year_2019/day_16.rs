@@ -1,6 +1,10 @@
+use anyhow::Context;
 use itertools::Itertools;
 
-use crate::utls::{MyParse, linalg::matrix::Matrix};
+use crate::{
+    puzzle::{Answer, Solution},
+    utls::{MyParse, linalg::matrix::Matrix, parse_digits},
+};
 
 fn part_1(input: &[i32], fft_mat: &Matrix<i32>) -> Vec<i32> {
     let mut input = Matrix::from(input.to_vec());
@@ -10,9 +14,38 @@ fn part_1(input: &[i32], fft_mat: &Matrix<i32>) -> Vec<i32> {
     input.column_vector()
 }
 
+fn part_2(input: &[i32]) -> anyhow::Result<Vec<i32>> {
+    let offset: usize = input
+        .get(0..7)
+        .context("signal shorter than message offset")?
+        .iter()
+        .map(|x| x.to_string())
+        .join("")
+        .parse()?;
+    let len = input.len();
+    anyhow::ensure!(
+        offset + 8 <= len * 10000,
+        "message offset {} is past the end of the signal",
+        offset
+    );
+    let input = input
+        .iter()
+        .copied()
+        .cycle()
+        .skip(offset)
+        .take(len * 10000 - offset)
+        .collect_vec();
+    let fft = precompute(input.len());
+    Ok(part_1(&input, &fft))
+}
+
+fn message(res: &[i32]) -> anyhow::Result<Answer> {
+    let message = res.get(0..8).context("signal shorter than the message")?;
+    Ok(message.iter().join("").into())
+}
+
 #[derive(Debug)]
-#[allow(unused)]
-struct ParseInput(Vec<i32>);
+pub struct ParseInput(Vec<i32>);
 
 pub fn fft(input: Matrix<i32>, fft: &Matrix<i32>) -> Matrix<i32> {
     let mut intermediate = fft * input;
@@ -36,31 +69,27 @@ pub fn precompute(len: usize) -> Matrix<i32> {
 }
 
 impl MyParse for ParseInput {
-    fn my_parse(s: &str) -> Self {
-        Self(s.trim().chars().map(|c| (c as u8 - b'0') as i32).collect())
+    fn my_parse(s: &str) -> anyhow::Result<Self> {
+        let digits = parse_digits(s)?;
+        Ok(Self(digits.into_iter().map(i32::from).collect()))
     }
 }
-pub fn run() {
-    let ParseInput(input) = crate::utls::read_text_from_file("2019", "16");
-    let fft = precompute(input.len());
-    let res = part_1(&input, &fft);
-    println!("The answer is {:?}", &res[0..8]);
-    let offset: usize = input[0..7]
-        .iter()
-        .map(|x| x.to_string())
-        .join("")
-        .parse()
-        .unwrap();
-    let len = input.len();
-    let input = input
-        .into_iter()
-        .cycle()
-        .skip(offset)
-        .take(len * 10000 - offset)
-        .collect_vec();
-    let fft = precompute(input.len());
-    let res = part_1(&input, &fft);
-    println!("The answer is {:?}", &res[0..8])
+pub struct Day16;
+
+impl Solution for Day16 {
+    type Input = ParseInput;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        ParseInput::my_parse(input)
+    }
+
+    fn part_1(ParseInput(input): &Self::Input) -> anyhow::Result<Answer> {
+        message(&part_1(input, &precompute(input.len())))
+    }
+
+    fn part_2(ParseInput(input): &Self::Input) -> anyhow::Result<Answer> {
+        message(&part_2(input)?)
+    }
 }
 
 #[cfg(test)]
@@ -69,7 +98,7 @@ mod test {
 
     #[test]
     fn test_solution() {
-        let ParseInput(input) = ParseInput::my_parse("12345678");
+        let ParseInput(input) = ParseInput::my_parse("12345678").unwrap();
         let fft_base = precompute(input.len());
 
         let desired = vec![
@@ -92,32 +121,50 @@ mod test {
             cur = fft(cur, &fft_base);
             assert_eq!(
                 cur.clone().column_vector(),
-                ParseInput::my_parse(expected).0
+                ParseInput::my_parse(expected).unwrap().0
             );
         }
     }
 
     #[test]
     fn test_solution_big() {
-        let ParseInput(input) = ParseInput::my_parse("80871224585914546619083218645595");
+        let ParseInput(input) = ParseInput::my_parse("80871224585914546619083218645595").unwrap();
         let fft_base = precompute(input.len());
         let res = part_1(&input, &fft_base);
-        assert_eq!(res[0..8], ParseInput::my_parse("24176176").0);
+        assert_eq!(res[0..8], ParseInput::my_parse("24176176").unwrap().0);
     }
 
     #[test]
     fn test_solution_big_two() {
-        let ParseInput(input) = ParseInput::my_parse("19617804207202209144916044189917");
+        let ParseInput(input) = ParseInput::my_parse("19617804207202209144916044189917").unwrap();
         let fft_base = precompute(input.len());
         let res = part_1(&input, &fft_base);
-        assert_eq!(res[0..8], ParseInput::my_parse("73745418").0);
+        assert_eq!(res[0..8], ParseInput::my_parse("73745418").unwrap().0);
     }
 
     #[test]
     fn test_solution_big_three() {
-        let ParseInput(input) = ParseInput::my_parse("69317163492948606335995924319873");
+        let ParseInput(input) = ParseInput::my_parse("69317163492948606335995924319873").unwrap();
         let fft_base = precompute(input.len());
         let res = part_1(&input, &fft_base);
-        assert_eq!(res[0..8], ParseInput::my_parse("52432133").0);
+        assert_eq!(res[0..8], ParseInput::my_parse("52432133").unwrap().0);
+    }
+
+    #[test]
+    fn test_short_signal() {
+        let ParseInput(input) = ParseInput::my_parse("123456").unwrap();
+        assert_eq!(
+            part_2(&input).unwrap_err().to_string(),
+            "signal shorter than message offset"
+        );
+        let ParseInput(input) = ParseInput::my_parse("9999999").unwrap();
+        assert_eq!(
+            part_2(&input).unwrap_err().to_string(),
+            "message offset 9999999 is past the end of the signal"
+        );
+        assert_eq!(
+            message(&input).unwrap_err().to_string(),
+            "signal shorter than the message"
+        );
     }
 }
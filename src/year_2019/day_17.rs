@@ -1,24 +1,28 @@
 use std::collections::HashSet;
 
+use anyhow::Context;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
 
 use crate::{
     program::{
         Program, Val,
-        io::{TryReadVal, TryWriteVal, View, infallible::Unused},
+        io::infallible::Unused,
+        terminal::{AsciiTerminal, Output},
     },
+    puzzle::{Answer, Solution},
     utls::{
+        MyParse,
         conversions::point_map,
         linalg::{CardinalDir, Point, RotateDir},
     },
 };
 
 const FUN_SIZE_LIMIT: usize = 20;
-fn part_1(program: Program) -> Val {
-    let scaffold = Scaffold::init(program);
+fn part_1(program: Program) -> anyhow::Result<Val> {
+    let scaffold = Scaffold::init(program)?;
     // find all points
-    scaffold
+    Ok(scaffold
         .grid
         .iter()
         .filter(|&&point| {
@@ -28,18 +32,18 @@ fn part_1(program: Program) -> Val {
             })
         })
         .map(|point| point.0 * point.1)
-        .sum()
+        .sum())
 }
 
-fn part_2(program: Program) {
-    let scaffold = Scaffold::init(program.clone());
+fn part_2(program: Program) -> anyhow::Result<Val> {
+    let scaffold = Scaffold::init(program.clone())?;
     let path = scaffold.path();
     // try every partition for fitting our criteria
     let ([a, b, c], main) = find_functions(path);
 
     // now, try to make the robot walk the solution
     let mut program = program;
-    program.code[0] = 2;
+    program.memory[0] = 2;
 
     let mut input = [&main, &a, &b, &c]
         .iter()
@@ -51,7 +55,8 @@ fn part_2(program: Program) {
         .interactive(false)
         .moves(input.chars().collect_vec())
         .build()
-        .run();
+        .run()?
+        .context("robot did not report the collected dust")
 }
 
 fn find_functions(path: Vec<String>) -> ([String; 3], String) {
@@ -142,39 +147,17 @@ impl RobotManager {
         RobotManagerBuilder::new(program)
     }
 
-    pub fn run(mut self) {
-        let view = View::new_char(self.moves);
-
-        fn val_to_char(val: Val) -> char {
-            (u8::try_from(val)).unwrap() as char
-        }
-        struct AsciiView {
-            view: View,
-            prev_written: char,
+    /// Walk the robot along its moves, returning the dust it collected
+    pub fn run(self) -> anyhow::Result<Option<Val>> {
+        let mut terminal = AsciiTerminal::new(self.program);
+        for line in String::from_iter(self.moves).lines() {
+            terminal.send_line(line)?;
         }
-        impl TryWriteVal for AsciiView {
-            fn try_write_val(&mut self, val: Val) -> Option<()> {
-                if val > 300 {
-                    println!("Answer is: {}", val);
-                } else {
-                    let cval = val_to_char(val);
-                    self.prev_written = cval;
-                    print!("{}", cval);
-                }
-
-                Some(())
-            }
-        }
-        impl TryReadVal for AsciiView {
-            fn try_read_val(&mut self) -> Option<Val> {
-                self.view.try_read_val()
-            }
-        }
-        let mut view = AsciiView {
-            view,
-            prev_written: 'X',
-        };
-        self.program.eval_joint(&mut view);
+        // everything but the dust count is the ascii camera feed
+        Ok(terminal.run()?.into_iter().find_map(|output| match output {
+            Output::Large(dust) => Some(dust),
+            Output::Line(_) => None,
+        }))
     }
 }
 
@@ -185,12 +168,12 @@ struct Scaffold {
 }
 
 impl Scaffold {
-    pub fn init(mut program: Program) -> Self {
+    pub fn init(mut program: Program) -> anyhow::Result<Self> {
         let mut raw_grid: Vec<Vec<char>> = Vec::new();
-        program.eval(&mut Unused, &mut raw_grid);
+        program.eval(&mut Unused, &mut raw_grid)?;
         let grid = point_map(raw_grid.clone());
 
-        Self {
+        Ok(Self {
             grid: grid
                 .iter()
                 .filter_map(|(k, v)| (*v == '#').then_some(*k))
@@ -200,7 +183,7 @@ impl Scaffold {
                 .find_map(|(k, v)| CardinalDir::try_from(*v).ok().map(|dir| (*k, dir)))
                 .unwrap(),
             raw_grid,
-        }
+        })
     }
 
     pub fn path(&self) -> Vec<String> {
@@ -255,9 +238,20 @@ impl std::fmt::Display for Scaffold {
     }
 }
 
-pub fn run() {
-    let input: Program = crate::utls::read_text_from_file("2019", "17");
-    let ans = part_1(input.clone());
-    println!("The ans is {}", ans);
-    part_2(input);
+pub struct Day17;
+
+impl Solution for Day17 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
 }
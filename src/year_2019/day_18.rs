@@ -10,7 +10,9 @@ use maplit::{hashmap, hashset};
 
 use crate::{
     maze::{self, Maze},
-    utls::linalg::Point,
+    program::Val,
+    puzzle::{Answer, Solution},
+    utls::{MyParse, linalg::Point},
 };
 
 #[derive(Default, Debug, Eq, PartialEq, Hash, Clone)]
@@ -224,22 +226,74 @@ fn part_1(maze: &Maze) -> usize {
     graph.astar(starts)
 }
 
+/// Wall off the entrance and its neighbors, placing a robot on each diagonal.
+/// Mazes which already have several robots are left alone.
+fn split_vault(maze: &Maze) -> Maze {
+    let mut maze = maze.clone();
+    let entrances = maze
+        .things
+        .iter()
+        .filter(|(_, val)| **val == '@')
+        .map(|(point, _)| *point)
+        .collect_vec();
+    let [entrance] = entrances[..] else {
+        return maze;
+    };
+    if maze
+        .things
+        .values()
+        .any(|val| ['&', '$', '%'].contains(val))
+    {
+        return maze;
+    }
+
+    let row_count = maze.underlying_grid.len() as Val;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let point = entrance + Point(dx, dy);
+            maze.things.remove(&point);
+            if dx == 0 || dy == 0 {
+                maze.points.remove(&point);
+                maze.underlying_grid[(row_count - point.1 - 1) as usize][point.0 as usize] =
+                    maze::Cell::Wall;
+            }
+        }
+    }
+    for (diagonal, robot) in [
+        (Point(-1, 1), '@'),
+        (Point(1, 1), '&'),
+        (Point(-1, -1), '$'),
+        (Point(1, -1), '%'),
+    ] {
+        maze.things.insert(entrance + diagonal, robot);
+    }
+    maze
+}
+
 fn part_2(maze: &Maze) -> usize {
-    part_1(maze)
+    part_1(&split_vault(maze))
 }
 
-pub fn run() {
-    let input: Maze = crate::utls::read_text_from_file("2019", "18");
-    // let res = part_1(&input);
-    // println!("The answer is: {}", res);
-    let res = part_2(&input);
-    println!("The answer is: {}", res);
+pub struct Day18;
+
+impl Solution for Day18 {
+    type Input = Maze;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Maze::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input).into())
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::utls::MyParse;
-
     use super::*;
 
     #[test]
@@ -248,7 +302,7 @@ mod test {
 #b.A.@.a#
 #########";
 
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let res = part_1(&maze);
         assert_eq!(res, 8);
     }
@@ -259,7 +313,7 @@ mod test {
 #b.A.@.a#
 #########";
 
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let res = part_1(&maze);
         assert_eq!(res, 8);
     }
@@ -276,8 +330,37 @@ mod test {
 #o#m..#i#jk.#
 #############";
 
-        let maze = Maze::my_parse(input);
+        let maze = Maze::my_parse(input).unwrap();
         let graph = Graph::from(&maze);
         println!("{:?}", graph);
     }
+
+    #[test]
+    fn test_split_vault() {
+        let maze = Maze::my_parse(
+            "#######
+#a.#Cd#
+##...##
+##.@.##
+##...##
+#cB#Ab#
+#######",
+        )
+        .unwrap();
+        let split = Maze::my_parse(
+            "#######
+#a.#Cd#
+##@#&##
+#######
+##$#%##
+#cB#Ab#
+#######",
+        )
+        .unwrap();
+        let same = |a: &Maze, b: &Maze| {
+            a.points == b.points && a.things == b.things && a.underlying_grid == b.underlying_grid
+        };
+        assert!(same(&split_vault(&maze), &split));
+        assert!(same(&split_vault(&split), &split));
+    }
 }
@@ -1,50 +1,53 @@
 use crate::{
     program::{Program, Val, io::View},
-    utls::linalg::Point,
+    puzzle::{Answer, Solution},
+    utls::{MyParse, linalg::Point},
 };
 
-fn part_1(program: Program) -> Val {
+fn part_1(program: Program) -> anyhow::Result<Val> {
     let mut sum = 0;
     for i in 0..50 {
         for j in 0..50 {
-            let mut input = View::new(vec![j, i]);
-            let mut output = 0;
-            program.clone().eval(&mut input, &mut output);
-            sum += output;
-            print!("{}", output);
+            sum += probe(&program, Point(j, i))?;
         }
-        println!();
     }
-    sum
+    Ok(sum)
 }
 
-fn in_beam(program: &Program, point: Point) -> bool {
+/// Deploy a drone to `point`, returning 1 if the beam pulls it
+fn probe(program: &Program, point: Point) -> anyhow::Result<Val> {
     let mut input = View::new(point.into());
     let mut output = 0;
-    program.clone().eval(&mut input, &mut output);
-    output == 1
+    program.clone().eval(&mut input, &mut output)?;
+    anyhow::ensure!(
+        input.consumed() == 2,
+        "drone read {} of its 2 coordinates",
+        input.consumed()
+    );
+    Ok(output)
 }
 
-fn part_2(program: Program) -> Point {
-    'row: for y in 100.. {
-        println!("Examining row: {}", y);
+fn in_beam(program: &Program, point: Point) -> anyhow::Result<bool> {
+    Ok(probe(program, point)? == 1)
+}
+
+fn part_2(program: Program) -> anyhow::Result<Point> {
+    const MAX_ROW: Val = 10_000;
+    'row: for y in 100..MAX_ROW {
         let mut found_beam = false;
         for x in 0..4 * y {
             let cur = Point(y, x);
-            if in_beam(&program, cur) {
+            if in_beam(&program, cur)? {
                 if !found_beam {
                     found_beam = true;
                 }
             } else if found_beam {
                 // have exited the beam
                 let upper_right = cur + Point(0, -1);
-                if x == 5 {
-                    println!("Looking for {}", upper_right);
-                }
                 // try for lower left
                 let lower_left = upper_right + Point(100, -100);
-                if in_beam(&program, lower_left) {
-                    return Point(lower_left.0, upper_right.1);
+                if in_beam(&program, lower_left)? {
+                    return Ok(Point(lower_left.0, upper_right.1));
                 } else {
                     continue 'row;
                 }
@@ -52,13 +55,27 @@ fn part_2(program: Program) -> Point {
         }
     }
 
-    unreachable!()
+    anyhow::bail!(
+        "the beam does not fit a 100x100 square in the first {} rows",
+        MAX_ROW
+    )
 }
 
-pub fn run() {
-    let input: Program = crate::utls::read_text_from_file("2019", "19");
-    let res = part_1(input.clone());
-    println!("Answer is {}", res);
-    let res = part_2(input);
-    println!("Answer is {}", res.0 * 10000 + res.1);
+pub struct Day19;
+
+impl Solution for Day19 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        let res = part_2(input.clone())?;
+        Ok((res.0 * 10000 + res.1).into())
+    }
 }
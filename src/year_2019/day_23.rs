@@ -0,0 +1,100 @@
+use anyhow::Context;
+
+use crate::{
+    program::{
+        Program, Val,
+        packet::{Nat, PacketNetwork},
+    },
+    puzzle::{Answer, Solution},
+    utls::MyParse,
+};
+
+const COMPUTERS: usize = 50;
+const NAT_ADDRESS: Val = 255;
+
+fn part_1(program: Program) -> anyhow::Result<Val> {
+    let mut network = PacketNetwork::new(&program, COMPUTERS)?;
+    loop {
+        if let Some(packet) = network.run_round()?.into_iter().next() {
+            anyhow::ensure!(
+                packet.dest == NAT_ADDRESS,
+                "packet sent to unknown address {}",
+                packet.dest
+            );
+            return Ok(packet.y);
+        }
+        anyhow::ensure!(
+            !network.is_idle(),
+            "network went idle without sending anything to {}",
+            NAT_ADDRESS
+        );
+    }
+}
+
+fn part_2(program: Program) -> anyhow::Result<Val> {
+    let mut network = PacketNetwork::new(&program, COMPUTERS)?;
+    let mut nat = Nat::new(NAT_ADDRESS);
+    let mut last_y = None;
+    loop {
+        let packets = network.run_round()?;
+        if let Some(packet) = nat.receive(packets).first() {
+            anyhow::bail!("packet sent to unknown address {}", packet.dest);
+        }
+        if network.is_idle() {
+            let packet = nat.wake(&mut network).with_context(|| {
+                format!(
+                    "network went idle without sending anything to {}",
+                    NAT_ADDRESS
+                )
+            })?;
+            if last_y == Some(packet.y) {
+                return Ok(packet.y);
+            }
+            last_y = Some(packet.y);
+        }
+    }
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = Program;
+
+    fn parse(input: &str) -> anyhow::Result<Self::Input> {
+        Program::my_parse(input)
+    }
+
+    fn part_1(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_1(input.clone())?.into())
+    }
+
+    fn part_2(input: &Self::Input) -> anyhow::Result<Answer> {
+        Ok(part_2(input.clone())?.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::program::asm::assemble;
+
+    #[test]
+    fn test_silent_network() {
+        let program = assemble(
+            "
+            poll:   in [x]
+                    jt 1, poll
+            x:      data 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            part_1(program.clone()).unwrap_err().to_string(),
+            "network went idle without sending anything to 255"
+        );
+        assert_eq!(
+            part_2(program).unwrap_err().to_string(),
+            "network went idle without sending anything to 255"
+        );
+    }
+}